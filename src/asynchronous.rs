@@ -0,0 +1,200 @@
+//! Asynchronous variants of [`Client`](crate::fetch::Client) and
+//! [`Mastodon`](crate::mastodon::Mastodon), based on `reqwest`. They must be
+//! used from within a Tokio runtime.
+//!
+//! Unlike their blocking counterparts, these do not retry failed requests
+//! nor cache responses.
+
+use crate::error::FetchError;
+use crate::fetch::{PAGE_SIZE, SEARCH_URL, USER_AGENT, check_number, is_html};
+use crate::http::Timeouts;
+use crate::mastodon::Status;
+use crate::oeis::{OeisEntry, OeisSequence, SearchPage};
+use crate::query::Query;
+use std::time::SystemTime;
+
+/// An asynchronous client for oeis.org.
+#[derive(Debug, Clone)]
+pub struct AsyncClient {
+    client: reqwest::Client,
+}
+
+impl Default for AsyncClient {
+    fn default() -> Self {
+        Self::with_config(Timeouts::default(), USER_AGENT)
+    }
+}
+
+impl AsyncClient {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a client with the given timeouts and User-Agent.
+    pub fn with_config(timeouts: Timeouts, user_agent: &str) -> Self {
+        Self {
+            client: build_client(timeouts, Some(user_agent)),
+        }
+    }
+
+    /// Fetch a sequence from oeis.org by its A-number.
+    ///
+    /// Fails with [`FetchError::Mismatch`] if the OEIS answers with another
+    /// sequence.
+    pub async fn fetch(&self, id: u64) -> Result<OeisSequence, FetchError> {
+        let entry = self
+            .search_page(&Query::new().id(id), 0)
+            .await?
+            .0
+            .into_iter()
+            .next()
+            .ok_or(FetchError::NotFound(id))?;
+        check_number(id, entry.number)?;
+        Ok(OeisSequence::try_from(entry)?.fetched_at(SystemTime::now()))
+    }
+
+    /// Search the OEIS, walking through all pages of results.
+    pub fn search(&self, query: &Query) -> AsyncSearchResults {
+        AsyncSearchResults {
+            client: self.clone(),
+            query: query.clone(),
+            start: 0,
+            page: Vec::new().into_iter(),
+            total: None,
+            done: false,
+        }
+    }
+
+    async fn search_page(
+        &self,
+        query: &Query,
+        start: usize,
+    ) -> Result<(Vec<OeisEntry>, Option<usize>), FetchError> {
+        let q = query.to_string();
+        let start = start.to_string();
+        let mut params = vec![("q", q.as_str()), ("fmt", "json"), ("start", &start)];
+        if let Some(sort) = query.sort_order() {
+            params.push(("sort", sort.as_str()));
+        }
+        let body = self
+            .client
+            .get(SEARCH_URL)
+            .query(&params)
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+        if is_html(&body) {
+            return Err(FetchError::ServiceUnavailable);
+        }
+        let page = SearchPage::from_json(&body)?;
+        Ok(page.into_parts())
+    }
+}
+
+/// The results of a search query, see
+/// [`SearchResults`](crate::fetch::SearchResults).
+#[derive(Debug)]
+pub struct AsyncSearchResults {
+    client: AsyncClient,
+    query: Query,
+    start: usize,
+    page: std::vec::IntoIter<OeisEntry>,
+    total: Option<usize>,
+    done: bool,
+}
+
+impl AsyncSearchResults {
+    /// Total number of matches for the query, usually known once the last
+    /// page has been fetched.
+    pub fn total(&self) -> Option<usize> {
+        self.total
+    }
+
+    /// The next result, fetching the next page if needed.
+    pub async fn next(&mut self) -> Option<Result<OeisSequence, FetchError>> {
+        loop {
+            if let Some(entry) = self.page.next() {
+                return Some(
+                    OeisSequence::try_from(entry)
+                        .map(|seq| seq.fetched_at(SystemTime::now()))
+                        .map_err(FetchError::from),
+                );
+            }
+            if self.done {
+                return None;
+            }
+            match self.client.search_page(&self.query, self.start).await {
+                Ok((entries, count)) => {
+                    if count.is_some() {
+                        self.total = count;
+                    }
+                    if entries.len() < PAGE_SIZE {
+                        self.done = true;
+                        self.total = Some(self.start + entries.len());
+                    }
+                    self.start += entries.len();
+                    self.page = entries.into_iter();
+                }
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+}
+
+/// An asynchronous variant of [`Mastodon`](crate::mastodon::Mastodon).
+#[derive(Debug, Clone)]
+pub struct AsyncMastodon {
+    instance_url: String,
+    token: String,
+    client: reqwest::Client,
+}
+
+impl AsyncMastodon {
+    /// `instance_url` is the base URL (e.g. `https://mastodon.social`).
+    /// `token` is a Bearer access token with `write:statuses` scope.
+    pub fn new(instance_url: &str, token: &str) -> Self {
+        Self {
+            instance_url: instance_url.trim_end_matches('/').to_owned(),
+            token: token.to_owned(),
+            client: build_client(Timeouts::default(), None),
+        }
+    }
+
+    /// Set the connect and read timeouts of requests.
+    pub fn with_timeouts(mut self, timeouts: Timeouts) -> Self {
+        self.client = build_client(timeouts, None);
+        self
+    }
+
+    /// Post a status.
+    pub async fn post_status(&self, status: &str) -> Result<Status, reqwest::Error> {
+        self.client
+            .post(format!("{}/api/v1/statuses", self.instance_url))
+            .bearer_auth(&self.token)
+            .form(&[("status", status)])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+}
+
+fn build_client(timeouts: Timeouts, user_agent: Option<&str>) -> reqwest::Client {
+    let mut builder = reqwest::Client::builder();
+    if let Some(connect) = timeouts.connect {
+        builder = builder.connect_timeout(connect);
+    }
+    if let Some(read) = timeouts.read {
+        builder = builder.read_timeout(read);
+    }
+    if let Some(user_agent) = user_agent {
+        builder = builder.user_agent(user_agent);
+    }
+    builder.build().expect("failed to build HTTP client")
+}
@@ -0,0 +1,85 @@
+use num_bigint::BigInt;
+use std::fmt;
+
+/// The terms of a sequence as listed in its b-file.
+///
+/// B-files are plain text files with one `n a(n)` pair per line, and
+/// usually contain many more terms than the JSON `data` field. See the
+/// [documentation](https://oeis.org/SubmitB.html) for details.
+#[derive(Debug, Clone)]
+pub struct BFile {
+    /// The A-number of the sequence.
+    pub number: u64,
+    /// The `(n, a(n))` pairs, in file order.
+    pub terms: Vec<(i64, BigInt)>,
+}
+
+/// A term of the JSON `data` field that disagrees with the b-file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TermMismatch {
+    /// Index `n` of the term.
+    pub index: i64,
+    /// Value in the JSON `data` field.
+    pub data: BigInt,
+    /// Value in the b-file, or `None` if the b-file has no term at `index`.
+    pub bfile: Option<BigInt>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ParseBFileError {
+    /// Line number (starting from 1) of the malformed line.
+    pub line: usize,
+    /// Content of the malformed line.
+    pub content: String,
+}
+
+impl fmt::Display for ParseBFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid b-file line {}: {:?}", self.line, self.content)
+    }
+}
+
+impl std::error::Error for ParseBFileError {}
+
+impl BFile {
+    /// Parse the contents of the b-file of sequence `number`. Blank lines
+    /// and comment lines starting with `#` are ignored.
+    pub fn parse(number: u64, s: &str) -> Result<Self, ParseBFileError> {
+        let mut terms = Vec::new();
+        for (i, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut fields = line.split_whitespace();
+            let term = match (fields.next(), fields.next()) {
+                (Some(n), Some(value)) => n.parse().ok().zip(value.parse().ok()),
+                _ => None,
+            };
+            let term = term.ok_or_else(|| ParseBFileError {
+                line: i + 1,
+                content: line.to_owned(),
+            })?;
+            terms.push(term);
+        }
+        Ok(Self { number, terms })
+    }
+
+    /// Number of terms in the contents `s` of a b-file, counted without
+    /// parsing them. Malformed lines are counted as terms.
+    pub fn count_terms(s: &str) -> usize {
+        s.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .count()
+    }
+
+    /// Number of terms in the b-file.
+    pub fn len(&self) -> usize {
+        self.terms.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+}
@@ -0,0 +1,256 @@
+use crate::clock::{Clock, SystemClock};
+use crate::error::FetchError;
+use crate::fetch::Client;
+use crate::oeis::OeisSequence;
+use lru::LruCache;
+use std::fs;
+use std::io;
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, UNIX_EPOCH};
+
+/// An in-memory least-recently-used cache in front of [`Client::fetch`], so
+/// that repeated lookups of the same sequence do not hit oeis.org.
+pub struct FetchCache {
+    client: Client,
+    entries: LruCache<u64, OeisSequence>,
+    hits: u64,
+    misses: u64,
+}
+
+impl FetchCache {
+    /// Create a cache holding at most `capacity` sequences.
+    pub fn new(client: Client, capacity: NonZeroUsize) -> Self {
+        Self {
+            client,
+            entries: LruCache::new(capacity),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Fetch a sequence by its A-number, from the cache if possible.
+    pub fn fetch(&mut self, id: u64) -> Result<OeisSequence, FetchError> {
+        if let Some(seq) = self.entries.get(&id) {
+            self.hits += 1;
+            return Ok(seq.clone());
+        }
+        self.misses += 1;
+        let seq = self.client.fetch(id)?;
+        self.entries.put(id, seq.clone());
+        Ok(seq)
+    }
+
+    /// Number of lookups served from the cache.
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Number of lookups that required a request to oeis.org.
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// Number of sequences currently in the cache.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Remove all sequences from the cache. Hit and miss counters are kept.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// An on-disk cache of raw OEIS responses, keyed by URL.
+///
+/// Responses younger than `ttl` are served without any request. Older ones
+/// are revalidated with `If-None-Match`/`If-Modified-Since`, so unchanged
+/// responses are not downloaded again. The cache is best-effort: failing to
+/// write to it does not make a request fail.
+#[derive(Debug, Clone)]
+pub struct HttpCache {
+    dir: PathBuf,
+    ttl: Duration,
+    clock: Arc<dyn Clock>,
+}
+
+/// A response loaded from the [`HttpCache`].
+pub(crate) struct CachedResponse {
+    pub body: String,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    /// Whether the response is younger than the TTL.
+    pub fresh: bool,
+}
+
+impl HttpCache {
+    /// Store responses in `dir`, which is created if needed.
+    pub fn new(dir: impl Into<PathBuf>, ttl: Duration) -> Self {
+        Self {
+            dir: dir.into(),
+            ttl,
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Use `clock` instead of the system clock to determine the age of
+    /// cached responses.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Cache key for a URL and its query parameters: the hex-encoded 64-bit
+    /// FNV-1a hash of the full URL, which is stable across builds.
+    pub(crate) fn key(url: &str, query: &[(&str, &str)]) -> String {
+        let mut full = url.to_owned();
+        for (i, (k, v)) in query.iter().enumerate() {
+            full.push(if i == 0 { '?' } else { '&' });
+            full.push_str(&format!("{k}={v}"));
+        }
+        let hash = full.bytes().fold(0xcbf29ce484222325u64, |h, b| {
+            (h ^ u64::from(b)).wrapping_mul(0x100000001b3)
+        });
+        format!("{hash:016x}")
+    }
+
+    /// Load a cached response. The time it was stored at and its
+    /// validators are kept in a separate `.meta` file next to the body.
+    pub(crate) fn load(&self, key: &str) -> Option<CachedResponse> {
+        let meta = fs::read_to_string(self.dir.join(format!("{key}.meta"))).ok()?;
+        let body = fs::read_to_string(self.dir.join(key)).ok()?;
+        let mut lines = meta.lines();
+        let stored_at: u64 = lines.next()?.parse().ok()?;
+        let age = self
+            .clock
+            .now()
+            .duration_since(UNIX_EPOCH + Duration::from_secs(stored_at))
+            .unwrap_or_default();
+        let mut validator = || lines.next().filter(|s| !s.is_empty()).map(str::to_owned);
+        Some(CachedResponse {
+            body,
+            etag: validator(),
+            last_modified: validator(),
+            fresh: age < self.ttl,
+        })
+    }
+
+    pub(crate) fn store(
+        &self,
+        key: &str,
+        body: &str,
+        etag: Option<&str>,
+        last_modified: Option<&str>,
+    ) {
+        let _ = fs::create_dir_all(&self.dir)
+            .and_then(|()| fs::write(self.dir.join(key), body))
+            .and_then(|()| self.write_meta(key, etag, last_modified));
+    }
+
+    /// Mark a cached response as fresh again after a successful
+    /// revalidation.
+    pub(crate) fn touch(&self, key: &str, cached: &CachedResponse) {
+        let _ = self.write_meta(key, cached.etag.as_deref(), cached.last_modified.as_deref());
+    }
+
+    fn write_meta(
+        &self,
+        key: &str,
+        etag: Option<&str>,
+        last_modified: Option<&str>,
+    ) -> io::Result<()> {
+        let stored_at = self
+            .clock
+            .now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let meta = format!(
+            "{stored_at}\n{}\n{}\n",
+            etag.unwrap_or(""),
+            last_modified.unwrap_or("")
+        );
+        fs::write(self.dir.join(format!("{key}.meta")), meta)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use std::process;
+
+    const TTL: Duration = Duration::from_secs(60 * 60);
+
+    /// A cache in an empty temporary directory, with its clock.
+    fn cache(name: &str) -> (HttpCache, Arc<MockClock>) {
+        let dir = std::env::temp_dir().join(format!("oeis_bot-{}-{name}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let clock = Arc::new(MockClock::new(
+            UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+        ));
+        let cache = HttpCache::new(dir, TTL).with_clock(clock.clone());
+        (cache, clock)
+    }
+
+    #[test]
+    fn expires_after_ttl() {
+        let (cache, clock) = cache("expires_after_ttl");
+        cache.store(
+            "key",
+            "body",
+            Some("\"v1\""),
+            Some("Mon, 01 Jan 2024 00:00:00 GMT"),
+        );
+
+        let cached = cache.load("key").unwrap();
+        assert_eq!(cached.body, "body");
+        assert!(cached.fresh);
+
+        clock.advance(TTL - Duration::from_secs(1));
+        assert!(cache.load("key").unwrap().fresh);
+
+        clock.advance(Duration::from_secs(1));
+        let cached = cache.load("key").unwrap();
+        assert!(!cached.fresh);
+        // Stale responses keep their validators for revalidation.
+        assert_eq!(cached.body, "body");
+        assert_eq!(cached.etag.as_deref(), Some("\"v1\""));
+        assert_eq!(
+            cached.last_modified.as_deref(),
+            Some("Mon, 01 Jan 2024 00:00:00 GMT")
+        );
+        let _ = fs::remove_dir_all(&cache.dir);
+    }
+
+    #[test]
+    fn revalidation_makes_fresh_again() {
+        let (cache, clock) = cache("revalidation_makes_fresh_again");
+        cache.store("key", "body", Some("\"v1\""), None);
+        clock.advance(TTL * 2);
+        let cached = cache.load("key").unwrap();
+        assert!(!cached.fresh);
+
+        cache.touch("key", &cached);
+        let cached = cache.load("key").unwrap();
+        assert!(cached.fresh);
+        assert_eq!(cached.etag.as_deref(), Some("\"v1\""));
+        assert_eq!(cached.last_modified, None);
+
+        clock.advance(TTL);
+        assert!(!cache.load("key").unwrap().fresh);
+        let _ = fs::remove_dir_all(&cache.dir);
+    }
+
+    #[test]
+    fn missing_entry() {
+        let (cache, _) = cache("missing_entry");
+        assert!(cache.load("key").is_none());
+    }
+}
@@ -0,0 +1,86 @@
+use crate::http::{HttpTransport, Request, Response, TransportError, TransportErrorKind};
+use rand::Rng;
+use std::io::{Cursor, Read};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// A transport injecting synthetic failures into the requests sent through
+/// it, to exercise error handling (e.g. retries) end to end. All failures
+/// are disabled initially.
+#[derive(Debug, Clone)]
+pub struct Chaos {
+    inner: Arc<dyn HttpTransport>,
+    error_rate: f64,
+    delay: Duration,
+    truncate_rate: f64,
+}
+
+impl Chaos {
+    pub fn new(inner: Arc<dyn HttpTransport>) -> Self {
+        Self {
+            inner,
+            error_rate: 0.0,
+            delay: Duration::ZERO,
+            truncate_rate: 0.0,
+        }
+    }
+
+    /// Answer a fraction `rate` (between 0 and 1) of requests with a
+    /// synthetic 500 status, without sending them. A rate that is not a
+    /// finite number is treated as 0.
+    pub fn with_error_rate(mut self, rate: f64) -> Self {
+        self.error_rate = probability(rate);
+        self
+    }
+
+    /// Wait for `delay` before sending each request.
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Cut the body of a fraction `rate` (between 0 and 1) of successful
+    /// responses at a random point. A rate that is not a finite number is
+    /// treated as 0.
+    pub fn with_truncate_rate(mut self, rate: f64) -> Self {
+        self.truncate_rate = probability(rate);
+        self
+    }
+}
+
+impl HttpTransport for Chaos {
+    fn send(&self, request: Request) -> Result<Response, TransportError> {
+        let mut rng = rand::rng();
+        if !self.delay.is_zero() {
+            thread::sleep(self.delay);
+        }
+        if rng.random_bool(self.error_rate) {
+            return Ok(Response {
+                status: 500,
+                headers: Vec::new(),
+                body: Box::new(std::io::empty()),
+            });
+        }
+        let mut response = self.inner.send(request)?;
+        if response.is_success() && rng.random_bool(self.truncate_rate) {
+            let mut body = Vec::new();
+            response
+                .body
+                .read_to_end(&mut body)
+                .map_err(|e| TransportError::new(TransportErrorKind::Io, e))?;
+            body.truncate(rng.random_range(0..body.len().max(1)));
+            response.body = Box::new(Cursor::new(body));
+        }
+        Ok(response)
+    }
+}
+
+/// `rate` clamped between 0 and 1, or 0 if it is not finite.
+fn probability(rate: f64) -> f64 {
+    if rate.is_finite() {
+        rate.clamp(0.0, 1.0)
+    } else {
+        0.0
+    }
+}
@@ -0,0 +1,213 @@
+//! Coarse categories of sequences, guessed from their names and keywords.
+
+use crate::oeis::{Keyword, OeisSequence};
+use std::fmt;
+
+/// A coarse category of sequences.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Category {
+    /// Prime numbers and related sequences.
+    Primes,
+    /// Integer partitions.
+    Partitions,
+    /// Graphs and trees.
+    Graphs,
+    /// Digits and representations in a base.
+    Digits,
+    /// Expansions of constants.
+    Constants,
+    /// Counting permutations, subsets, paths, etc.
+    Combinatorics,
+}
+
+impl Category {
+    pub const ALL: &'static [Category] = &[
+        Category::Primes,
+        Category::Partitions,
+        Category::Graphs,
+        Category::Digits,
+        Category::Constants,
+        Category::Combinatorics,
+    ];
+
+    /// Hashtag for posts about sequences of the category.
+    pub fn hashtag(self) -> &'static str {
+        match self {
+            Category::Primes => "#PrimeNumbers",
+            Category::Partitions => "#Partitions",
+            Category::Graphs => "#GraphTheory",
+            Category::Digits => "#Digits",
+            Category::Constants => "#Constants",
+            Category::Combinatorics => "#Combinatorics",
+        }
+    }
+
+    /// Keywords of the OEIS implying the category.
+    fn keywords(self) -> &'static [Keyword] {
+        match self {
+            Category::Digits => &[Keyword::Base],
+            Category::Constants => &[Keyword::Cons, Keyword::Cofr],
+            Category::Combinatorics => &[Keyword::Walk],
+            Category::Primes | Category::Partitions | Category::Graphs => &[],
+        }
+    }
+
+    /// Words of sequence names suggesting the category, in lowercase.
+    fn words(self) -> &'static [&'static str] {
+        match self {
+            Category::Primes => &["prime", "semiprime"],
+            Category::Partitions => &["partition"],
+            Category::Graphs => &["graph", "tree", "vertices", "vertex", "edges"],
+            Category::Digits => &["digit", "base 2", "binary", "palindrom"],
+            Category::Constants => &["decimal expansion", "continued fraction"],
+            Category::Combinatorics => &[
+                "permutation",
+                "subset",
+                "composition",
+                "binomial",
+                "catalan",
+                "dyck",
+                "lattice path",
+                "number of ways",
+            ],
+        }
+    }
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Category::Primes => "primes",
+            Category::Partitions => "partitions",
+            Category::Graphs => "graphs",
+            Category::Digits => "digits",
+            Category::Constants => "constants",
+            Category::Combinatorics => "combinatorics",
+        };
+        f.write_str(name)
+    }
+}
+
+/// The categories of `seq`, in the order of [`Category::ALL`]. A sequence
+/// may have several categories, or none.
+pub fn classify(seq: &OeisSequence) -> Vec<Category> {
+    let name = seq.name.to_lowercase();
+    Category::ALL
+        .iter()
+        .copied()
+        .filter(|category| {
+            category.keywords().iter().any(|kw| seq.has_keyword(kw))
+                || category.words().iter().any(|word| name.contains(word))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::internal::parse_internal;
+
+    /// A sequence with the given number, name and keywords, and a few
+    /// terms.
+    fn sequence(number: u64, name: &str, keywords: &str) -> OeisSequence {
+        let text = format!(
+            "%S A{number:06} 1,2,3,4,5\n%N A{number:06} {name}\n%K A{number:06} {keywords}\n%O A{number:06} 1,2\n"
+        );
+        let entry = parse_internal(&text).unwrap().remove(0);
+        OeisSequence::try_from(entry).unwrap()
+    }
+
+    /// Sequences of the OEIS, with abridged names and keywords, and their
+    /// expected categories.
+    #[test]
+    fn classify_examples() {
+        use Category::*;
+        let examples: &[(u64, &str, &str, &[Category])] = &[
+            (40, "The prime numbers.", "core,nonn,nice,easy", &[Primes]),
+            (
+                1358,
+                "Semiprimes (or biprimes): products of two primes.",
+                "nonn,nice",
+                &[Primes],
+            ),
+            (
+                41,
+                "a(n) is the number of partitions of n (the partition numbers).",
+                "core,nonn,nice,easy",
+                &[Partitions],
+            ),
+            (
+                88,
+                "Number of graphs on n unlabeled nodes.",
+                "nonn,core,nice,hard",
+                &[Graphs],
+            ),
+            (
+                81,
+                "Number of unlabeled rooted trees with n nodes (or connected functions with a fixed point).",
+                "nonn,core,easy,nice",
+                &[Graphs],
+            ),
+            (
+                120,
+                "Binary weight of n.",
+                "nonn,easy,core,nice,base",
+                &[Digits],
+            ),
+            (
+                2113,
+                "Palindromes in base 10.",
+                "nonn,base,easy,nice",
+                &[Digits],
+            ),
+            (
+                796,
+                "Decimal expansion of Pi (or digits of Pi).",
+                "cons,nonn,core,easy",
+                &[Digits, Constants],
+            ),
+            (
+                1203,
+                "Simple continued fraction expansion of Pi.",
+                "cons,nonn,core,nice",
+                &[Constants],
+            ),
+            (
+                108,
+                "Catalan numbers: C(n) = binomial(2n,n)/(n+1) = (2n)!/(n!(n+1)!).",
+                "core,nonn,easy,eigen,nice",
+                &[Combinatorics],
+            ),
+            (
+                142,
+                "Factorial numbers n! = 1*2*3*4*...*n (order of symmetric group S_n, number of permutations of n letters).",
+                "core,easy,nonn,nice",
+                &[Combinatorics],
+            ),
+            (
+                1006,
+                "Motzkin numbers: number of ways of drawing any number of nonintersecting chords joining n (labeled) points on a circle.",
+                "nonn,core,nice,easy",
+                &[Combinatorics],
+            ),
+            (5043, "Riordan numbers.", "nonn,walk", &[Combinatorics]),
+            (7504, "Sum of the first n primes.", "nonn,easy", &[Primes]),
+            (
+                45,
+                "Fibonacci numbers: F(n) = F(n-1) + F(n-2) with F(0) = 0 and F(1) = 1.",
+                "core,nonn,nice,easy",
+                &[],
+            ),
+            (
+                27,
+                "The positive integers.",
+                "core,nonn,easy,mult,tabl",
+                &[],
+            ),
+        ];
+        for &(number, name, keywords, expected) in examples {
+            let seq = sequence(number, name, keywords);
+            assert_eq!(classify(&seq), expected, "A{number:06}: {name}");
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use crate::error::{FetchError, SelectionError};
+use crate::fetch::{Client, REJECTED_KEYWORD_SET};
+use crate::oeis::{ANumber, OeisSequence};
+use crate::query::Query;
+use num_bigint::BigInt;
+use rand::seq::IteratorRandom;
+use std::collections::BTreeMap;
+
+/// The operations of an OEIS client, so that code using them can be tested
+/// against [`MemoryClient`] instead of oeis.org.
+pub trait OeisClient {
+    /// Fetch a sequence by its A-number.
+    fn fetch(&self, id: u64) -> Result<OeisSequence, FetchError>;
+
+    /// Iterate over all the results of a search query.
+    fn search<'a>(
+        &'a self,
+        query: &Query,
+    ) -> Box<dyn Iterator<Item = Result<OeisSequence, FetchError>> + 'a>;
+
+    /// Pick a random sequence, skipping sequences that are probably
+    /// uninteresting.
+    fn random(&self) -> Result<OeisSequence, SelectionError>;
+}
+
+impl OeisClient for Client {
+    fn fetch(&self, id: u64) -> Result<OeisSequence, FetchError> {
+        Client::fetch(self, id)
+    }
+
+    fn search<'a>(
+        &'a self,
+        query: &Query,
+    ) -> Box<dyn Iterator<Item = Result<OeisSequence, FetchError>> + 'a> {
+        Box::new(Client::search(self, query))
+    }
+
+    fn random(&self) -> Result<OeisSequence, SelectionError> {
+        self.fetch_random()
+    }
+}
+
+/// An OEIS client serving a fixed set of sequences from memory, without
+/// any network access.
+///
+/// Searches support the clauses built by [`Query`]; raw clauses are matched
+/// against the names of sequences.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryClient {
+    sequences: BTreeMap<u64, OeisSequence>,
+}
+
+impl MemoryClient {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `seq`, replacing any sequence with the same A-number.
+    pub fn insert(&mut self, seq: OeisSequence) {
+        self.sequences.insert(seq.number, seq);
+    }
+}
+
+impl FromIterator<OeisSequence> for MemoryClient {
+    fn from_iter<I: IntoIterator<Item = OeisSequence>>(iter: I) -> Self {
+        let mut client = Self::new();
+        for seq in iter {
+            client.insert(seq);
+        }
+        client
+    }
+}
+
+impl OeisClient for MemoryClient {
+    fn fetch(&self, id: u64) -> Result<OeisSequence, FetchError> {
+        self.sequences
+            .get(&id)
+            .cloned()
+            .ok_or(FetchError::NotFound(id))
+    }
+
+    fn search<'a>(
+        &'a self,
+        query: &Query,
+    ) -> Box<dyn Iterator<Item = Result<OeisSequence, FetchError>> + 'a> {
+        let clauses = query.clauses().to_vec();
+        Box::new(
+            self.sequences
+                .values()
+                .filter(move |seq| clauses.iter().all(|clause| matches(seq, clause)))
+                .cloned()
+                .map(Ok),
+        )
+    }
+
+    /// Fails with [`FetchError::NoResults`] if there is no eligible sequence.
+    fn random(&self) -> Result<OeisSequence, SelectionError> {
+        self.sequences
+            .values()
+            .filter(|seq| !seq.keyword_set().intersects(REJECTED_KEYWORD_SET))
+            .choose(&mut rand::rng())
+            .cloned()
+            .ok_or_else(|| FetchError::NoResults(String::new()).into())
+    }
+}
+
+/// Whether `seq` matches a single query clause.
+fn matches(seq: &OeisSequence, clause: &str) -> bool {
+    let contains = |field: &str, value: &str| field.to_lowercase().contains(&value.to_lowercase());
+    let has_keyword = |value: &str| {
+        let Ok(keyword) = value.parse();
+        seq.has_keyword(&keyword)
+    };
+    let clause = clause.trim_matches('"');
+    if let Some(value) = clause.strip_prefix("-keyword:") {
+        !has_keyword(value)
+    } else if let Some(value) = clause.strip_prefix("keyword:") {
+        has_keyword(value)
+    } else if let Some(ids) = clause.strip_prefix("id:") {
+        ids.split('|').any(|id| match id.parse::<ANumber>() {
+            Ok(a) => a.0 == seq.number,
+            Err(_) => seq
+                .id
+                .as_deref()
+                .is_some_and(|hids| hids.split_whitespace().any(|h| h == id)),
+        })
+    } else if let Some(value) = clause.strip_prefix("author:") {
+        contains(&seq.author, value.trim_matches('"'))
+    } else if let Some(value) = clause.strip_prefix("name:") {
+        contains(&seq.name, value.trim_matches('"'))
+    } else if let Some(terms) = parse_terms(clause) {
+        seq.data.windows(terms.len()).any(|w| w == terms)
+    } else {
+        contains(&seq.name, clause)
+    }
+}
+
+/// Parse a comma-separated list of terms, e.g. `1,1,2,3,5`.
+fn parse_terms(clause: &str) -> Option<Vec<BigInt>> {
+    let terms = clause
+        .split(',')
+        .map(|t| t.trim().parse().ok())
+        .collect::<Option<Vec<BigInt>>>()?;
+    (!terms.is_empty()).then_some(terms)
+}
@@ -1,16 +1,101 @@
+use crate::bfile::ParseBFileError;
+use crate::http::TransportError;
+use crate::internal::ParseInternalError;
+use crate::mirror::ParseDumpError;
+use crate::oeis::{ANumber, ConvertError};
 use std::fmt;
+use std::io;
+use std::time::Duration;
 
 #[derive(Debug)]
 pub enum FetchError {
-    Http(ureq::Error),
+    Transport(TransportError),
+    /// The server answered with an unexpected HTTP status.
+    Status(u16),
+    /// The server is overloaded or rate-limits the client (status 429 or
+    /// 503), and may tell how long to wait before retrying.
+    RateLimited {
+        retry_after: Option<Duration>,
+    },
+    /// The server answered with an HTML page (e.g. during maintenance)
+    /// instead of the requested data.
+    ServiceUnavailable,
+    /// The response body could not be read.
+    Io(io::Error),
+    #[cfg(feature = "async")]
+    AsyncHttp(reqwest::Error),
+    #[cfg(feature = "wasm")]
+    WasmHttp(gloo_net::Error),
+    Json(serde_json::Error),
     NotFound(u64),
+    /// The OEIS answered with another sequence than the requested one.
+    Mismatch {
+        requested: u64,
+        got: u64,
+    },
+    HandbookIdNotFound(String),
+    /// No sequence matches the query.
+    NoResults(String),
+    /// The number of results of a search could not be found in the
+    /// response.
+    MissingCount,
+    InvalidBFile(ParseBFileError),
+    InvalidInternalFormat(ParseInternalError),
+    InvalidDump(ParseDumpError),
+    /// An entry returned by the OEIS has a malformed field.
+    InvalidEntry(ConvertError),
 }
 
 impl fmt::Display for FetchError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            FetchError::Http(e) => write!(f, "HTTP error: {e}"),
-            FetchError::NotFound(id) => write!(f, "sequence A{id:06} not found"),
+            FetchError::Transport(e) => write!(f, "HTTP error: {e}"),
+            FetchError::Status(status) => write!(f, "HTTP error: status {status}"),
+            FetchError::RateLimited {
+                retry_after: Some(delay),
+            } => write!(f, "rate limited, retry after {}s", delay.as_secs()),
+            FetchError::RateLimited { retry_after: None } => write!(f, "rate limited"),
+            FetchError::ServiceUnavailable => {
+                write!(f, "service unavailable: received an HTML page")
+            }
+            FetchError::Io(e) => write!(f, "I/O error while reading response: {e}"),
+            #[cfg(feature = "async")]
+            FetchError::AsyncHttp(e) => write!(f, "HTTP error: {e}"),
+            #[cfg(feature = "wasm")]
+            FetchError::WasmHttp(e) => write!(f, "HTTP error: {e}"),
+            FetchError::Json(e) => write!(f, "invalid JSON response: {e}"),
+            FetchError::NotFound(id) => write!(f, "sequence {} not found", ANumber(*id)),
+            FetchError::Mismatch { requested, got } => {
+                write!(
+                    f,
+                    "requested sequence {} but got {}",
+                    ANumber(*requested),
+                    ANumber(*got)
+                )
+            }
+            FetchError::HandbookIdNotFound(id) => {
+                write!(f, "no sequence with handbook ID {id} found")
+            }
+            FetchError::NoResults(query) => write!(f, "no sequence matches {query:?}"),
+            FetchError::MissingCount => write!(f, "no result count in the search response"),
+            FetchError::InvalidBFile(e) => write!(f, "{e}"),
+            FetchError::InvalidInternalFormat(e) => write!(f, "{e}"),
+            FetchError::InvalidDump(e) => write!(f, "{e}"),
+            FetchError::InvalidEntry(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl FetchError {
+    /// Whether the error is likely temporary, so that the request may
+    /// succeed if retried.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            FetchError::Transport(e) => e.is_transient(),
+            FetchError::Status(status) => *status >= 500,
+            FetchError::RateLimited { .. } | FetchError::ServiceUnavailable => true,
+            FetchError::Io(_) => true,
+            _ => false,
         }
     }
 }
@@ -18,14 +103,131 @@ impl fmt::Display for FetchError {
 impl std::error::Error for FetchError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
-            FetchError::Http(e) => Some(e),
-            FetchError::NotFound(_) => None,
+            FetchError::Transport(e) => Some(e),
+            FetchError::Io(e) => Some(e),
+            #[cfg(feature = "async")]
+            FetchError::AsyncHttp(e) => Some(e),
+            #[cfg(feature = "wasm")]
+            FetchError::WasmHttp(e) => Some(e),
+            FetchError::Json(e) => Some(e),
+            FetchError::InvalidBFile(e) => Some(e),
+            FetchError::InvalidInternalFormat(e) => Some(e),
+            FetchError::InvalidDump(e) => Some(e),
+            FetchError::InvalidEntry(e) => Some(e),
+            FetchError::Status(_)
+            | FetchError::RateLimited { .. }
+            | FetchError::ServiceUnavailable
+            | FetchError::NotFound(_)
+            | FetchError::Mismatch { .. }
+            | FetchError::HandbookIdNotFound(_)
+            | FetchError::NoResults(_)
+            | FetchError::MissingCount => None,
+        }
+    }
+}
+
+impl From<TransportError> for FetchError {
+    fn from(e: TransportError) -> Self {
+        FetchError::Transport(e)
+    }
+}
+
+impl From<io::Error> for FetchError {
+    fn from(e: io::Error) -> Self {
+        FetchError::Io(e)
+    }
+}
+
+#[cfg(feature = "async")]
+impl From<reqwest::Error> for FetchError {
+    fn from(e: reqwest::Error) -> Self {
+        FetchError::AsyncHttp(e)
+    }
+}
+
+#[cfg(feature = "wasm")]
+impl From<gloo_net::Error> for FetchError {
+    fn from(e: gloo_net::Error) -> Self {
+        FetchError::WasmHttp(e)
+    }
+}
+
+impl From<serde_json::Error> for FetchError {
+    fn from(e: serde_json::Error) -> Self {
+        FetchError::Json(e)
+    }
+}
+
+impl From<ParseBFileError> for FetchError {
+    fn from(e: ParseBFileError) -> Self {
+        FetchError::InvalidBFile(e)
+    }
+}
+
+impl From<ParseInternalError> for FetchError {
+    fn from(e: ParseInternalError) -> Self {
+        FetchError::InvalidInternalFormat(e)
+    }
+}
+
+impl From<ParseDumpError> for FetchError {
+    fn from(e: ParseDumpError) -> Self {
+        FetchError::InvalidDump(e)
+    }
+}
+
+impl From<ConvertError> for FetchError {
+    fn from(e: ConvertError) -> Self {
+        FetchError::InvalidEntry(e)
+    }
+}
+
+/// Failure to pick a random sequence.
+#[derive(Debug)]
+pub enum SelectionError {
+    /// No acceptable sequence was found within the maximum number of
+    /// attempts.
+    Exhausted(SelectionStats),
+    Fetch(FetchError),
+}
+
+/// Outcome of the attempts made to pick a random sequence.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SelectionStats {
+    /// Number of sequences requested.
+    pub attempts: u32,
+    /// Number of requested A-numbers that do not exist.
+    pub not_found: u32,
+    /// Number of sequences skipped because of their keywords.
+    pub rejected: u32,
+    /// Number of sequences skipped because their entry is malformed.
+    pub invalid: u32,
+}
+
+impl fmt::Display for SelectionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SelectionError::Exhausted(stats) => write!(
+                f,
+                "no acceptable sequence found after {} attempts ({} not found, {} rejected, {} invalid)",
+                stats.attempts, stats.not_found, stats.rejected, stats.invalid
+            ),
+            SelectionError::Fetch(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for SelectionError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SelectionError::Fetch(e) => Some(e),
+            SelectionError::Exhausted(_) => None,
         }
     }
 }
 
-impl From<ureq::Error> for FetchError {
-    fn from(e: ureq::Error) -> Self {
-        FetchError::Http(e)
+impl From<FetchError> for SelectionError {
+    fn from(e: FetchError) -> Self {
+        SelectionError::Fetch(e)
     }
 }
@@ -1,46 +1,907 @@
-use crate::error::FetchError;
-use crate::oeis::{Keyword, OeisEntry, OeisSequence};
+use crate::bfile::BFile;
+use crate::cache::HttpCache;
+use crate::clock::{Clock, SystemClock};
+use crate::error::{FetchError, SelectionError, SelectionStats};
+use crate::http::{self, HttpConfig, HttpTransport, Request, Response, TransportError};
+use crate::internal::parse_internal;
+use crate::limit::HostLimits;
+use crate::oeis::{ANumber, Keyword, KeywordSet, OeisEntry, OeisSequence, SearchPage};
+use crate::query::{Query, Sort};
+use crate::retry::RetryPolicy;
+use crate::xref::{self, XrefGraph, XrefLimits};
+use num_bigint::BigInt;
 use rand::Rng;
+use std::collections::{BTreeMap, HashMap};
+use std::io::Read;
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
 
-const MAX_SEQUENCE_ID: u64 = 380_000;
+pub(crate) const SEARCH_URL: &str = "https://oeis.org/search";
 
-const REJECTED_KEYWORDS: &[Keyword] = &[
+/// Number of entries returned per page of search results.
+pub(crate) const PAGE_SIZE: usize = 10;
+
+/// Default User-Agent of requests to oeis.org.
+pub(crate) const USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+/// Maximum number of concurrent requests to oeis.org by default.
+pub const OEIS_MAX_CONCURRENT: NonZeroUsize = NonZeroUsize::new(2).unwrap();
+
+/// Maximum number of sequences requested by [`Client::fetch_random`].
+pub const MAX_RANDOM_ATTEMPTS: u32 = 100;
+
+/// Upper bound of A-numbers used when the latest one cannot be discovered.
+pub(crate) const MAX_SEQUENCE_ID: u64 = 380_000;
+
+/// How long a discovered maximum A-number is reused.
+pub(crate) const MAX_SEQUENCE_ID_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+pub(crate) const REJECTED_KEYWORDS: &[Keyword] = &[
+    Keyword::Allocated,
     Keyword::Dead,
     Keyword::Dumb,
     Keyword::Dupe,
     Keyword::Less,
     Keyword::Obsc,
     Keyword::Probation,
+    Keyword::Recycled,
     Keyword::Uned,
 ];
 
-/// Fetch a sequence from oeis.org by its A-number (e.g. `fetch(250000)`
-/// retrieves A250000).
-pub fn fetch(id: u64) -> Result<OeisSequence, FetchError> {
-    let entries: Vec<OeisEntry> = ureq::get("https://oeis.org/search")
-        .query("q", format!("id:A{id:06}"))
-        .query("fmt", "json")
-        .call()?
-        .body_mut()
-        .read_json()?;
-    let entry = entries.into_iter().next().ok_or(FetchError::NotFound(id))?;
-    Ok(OeisSequence::from(entry))
-}
-
-/// Fetch a random sequence from the OEIS, excluding sequences with
-/// one of the rejected keywords.
-pub fn fetch_random() -> OeisSequence {
-    let mut rng = rand::rng();
-    loop {
-        let id = rng.random_range(1..=MAX_SEQUENCE_ID);
-        let seq = match fetch(id) {
-            Ok(seq) => seq,
-            Err(FetchError::NotFound(_)) => continue,
-            Err(e) => panic!("{e}"),
+/// [`REJECTED_KEYWORDS`] as a set.
+pub(crate) const REJECTED_KEYWORD_SET: KeywordSet = KeywordSet::from_keywords(REJECTED_KEYWORDS);
+
+/// A client for oeis.org.
+///
+/// Connections are reused across requests made with the client, including
+/// by its clones, which share at most [`OEIS_MAX_CONCURRENT`] concurrent
+/// requests to oeis.org. Responses are requested gzip-compressed and decompressed
+/// transparently.
+#[derive(Debug, Clone)]
+pub struct Client {
+    http_cache: Option<HttpCache>,
+    retry: RetryPolicy,
+    transport: Arc<dyn HttpTransport>,
+    user_agent: String,
+    clock: Arc<dyn Clock>,
+    /// Discovered maximum A-number, and when it was discovered.
+    max_id: Arc<Mutex<Option<(u64, SystemTime)>>>,
+    /// Number of calls to [`Client::fetch_random`] by number of attempts.
+    random_attempts: Arc<Mutex<BTreeMap<u32, u64>>>,
+}
+
+impl Default for Client {
+    fn default() -> Self {
+        Self {
+            http_cache: None,
+            retry: RetryPolicy::default(),
+            transport: Arc::new(
+                HostLimits::new(http::default_transport())
+                    .with_limit("oeis.org", OEIS_MAX_CONCURRENT),
+            ),
+            user_agent: USER_AGENT.to_owned(),
+            clock: Arc::new(SystemClock),
+            max_id: Arc::default(),
+            random_attempts: Arc::default(),
+        }
+    }
+}
+
+impl Client {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Retry failed requests according to `policy`.
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    /// Send requests with `transport`. It may be shared with other clients,
+    /// e.g. [`Mastodon`](crate::mastodon::Mastodon). The number of concurrent
+    /// requests is not limited unless `transport` is a [`HostLimits`].
+    pub fn with_transport(mut self, transport: Arc<dyn HttpTransport>) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    /// Send requests with the default transport, configured with `config`
+    /// (timeouts and proxy) and limited to [`OEIS_MAX_CONCURRENT`] concurrent
+    /// requests to oeis.org. Fails if the proxy URL is invalid.
+    pub fn with_http_config(self, config: &HttpConfig) -> Result<Self, TransportError> {
+        let transport =
+            HostLimits::new(http::transport(config)?).with_limit("oeis.org", OEIS_MAX_CONCURRENT);
+        Ok(self.with_transport(Arc::new(transport)))
+    }
+
+    /// Set the User-Agent of requests. It defaults to the crate name and
+    /// version.
+    pub fn with_user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = user_agent.to_owned();
+        self
+    }
+
+    /// Add contact information of the operator (e.g. an email address or
+    /// a profile URL) to the default User-Agent, as the OEIS asks automated
+    /// clients to identify themselves.
+    pub fn with_contact(self, contact: &str) -> Self {
+        self.with_user_agent(&format!("{USER_AGENT} (+{contact})"))
+    }
+
+    /// Use `clock` instead of the system clock to wait between retries and
+    /// to expire the discovered maximum A-number.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Cache raw responses on disk, see [`HttpCache`].
+    pub fn with_http_cache(mut self, cache: HttpCache) -> Self {
+        self.http_cache = Some(cache);
+        self
+    }
+
+    /// Fetch a sequence from oeis.org by its A-number (e.g.
+    /// `client.fetch(250000)` retrieves A250000).
+    ///
+    /// Fails with [`FetchError::Mismatch`] if the OEIS answers with another
+    /// sequence.
+    pub fn fetch(&self, id: u64) -> Result<OeisSequence, FetchError> {
+        let seq = self.fetch_lenient(id)?;
+        check_number(id, seq.number)?;
+        Ok(seq)
+    }
+
+    /// Fetch a sequence like [`Client::fetch`], but return the first result
+    /// of the search even if it is not the requested sequence.
+    pub fn fetch_lenient(&self, id: u64) -> Result<OeisSequence, FetchError> {
+        let entry = self
+            .search_page(&Query::new().id(id), 0)?
+            .into_iter()
+            .next()
+            .ok_or(FetchError::NotFound(id))?;
+        self.sequence(entry)
+    }
+
+    /// Fetch the b-file of a sequence, which usually lists many more terms
+    /// than [`Client::fetch`] returns.
+    pub fn fetch_bfile(&self, id: u64) -> Result<BFile, FetchError> {
+        let text = self.get_bfile_text(id)?;
+        Ok(BFile::parse(id, &text)?)
+    }
+
+    /// Number of known terms of a sequence, counted in its b-file without
+    /// parsing them, see [`BFile::count_terms`].
+    pub fn fetch_term_count(&self, id: u64) -> Result<usize, FetchError> {
+        let text = self.get_bfile_text(id)?;
+        Ok(BFile::count_terms(&text))
+    }
+
+    fn get_bfile_text(&self, id: u64) -> Result<String, FetchError> {
+        self.get_text(&ANumber(id).b_file_url(), &[])
+    }
+
+    /// Fetch the PNG plot of a sequence generated by the OEIS, as raw image
+    /// bytes.
+    pub fn fetch_graph(&self, id: u64) -> Result<Vec<u8>, FetchError> {
+        self.get_bytes(&format!("{}/graph", ANumber(id).url()), &[("png", "1")])
+    }
+
+    /// Fetch the MIDI rendering of a sequence generated by the OEIS "listen"
+    /// feature, as raw bytes. Mostly of interest for sequences with the
+    /// [`Keyword::Hear`] keyword.
+    pub fn fetch_midi(&self, id: u64) -> Result<Vec<u8>, FetchError> {
+        self.get_bytes(
+            "https://oeis.org/play",
+            &[("seq", &ANumber(id).to_string())],
+        )
+    }
+
+    /// Fetch a sequence by its old-style handbook ID from the Handbook of
+    /// Integer Sequences (N-numbers) or the Encyclopedia of Integer Sequences
+    /// (M-numbers), e.g. `client.fetch_by_handbook_id("M0692")`.
+    pub fn fetch_by_handbook_id(&self, id: &str) -> Result<OeisSequence, FetchError> {
+        let entry = self
+            .search_page(&Query::new().handbook_id(id), 0)?
+            .into_iter()
+            .find(|e| {
+                e.id.as_deref()
+                    .is_some_and(|ids| ids.split_whitespace().any(|s| s.eq_ignore_ascii_case(id)))
+            })
+            .ok_or_else(|| FetchError::HandbookIdNotFound(id.to_owned()))?;
+        self.sequence(entry)
+    }
+
+    /// Fetch several sequences at once, keyed by A-number. Sequences that do
+    /// not exist or whose entry is malformed are missing from the result.
+    pub fn fetch_many(&self, ids: &[u64]) -> Result<HashMap<u64, OeisSequence>, FetchError> {
+        if ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        self.search(&Query::new().ids(ids))
+            .filter(|seq| !matches!(seq, Err(FetchError::InvalidEntry(_))))
+            .map(|seq| seq.map(|seq| (seq.number, seq)))
+            .collect()
+    }
+
+    /// Fetch the sequences cross-referenced by `seq`, then those referenced
+    /// by them, and so on, within `limits`. Each level is fetched with a
+    /// single search. Sequences whose entry is malformed are left out.
+    pub fn fetch_xrefs(
+        &self,
+        seq: &OeisSequence,
+        limits: XrefLimits,
+    ) -> Result<XrefGraph, FetchError> {
+        let mut graph = XrefGraph {
+            root: seq.number,
+            ..Default::default()
+        };
+        graph.sequences.insert(seq.number, seq.clone());
+        let mut frontier = vec![seq.number];
+        let mut budget = limits.max_sequences;
+        for _ in 0..limits.depth {
+            let mut next = Vec::new();
+            for id in frontier {
+                let ids = xref::xref_ids(&graph.sequences[&id]);
+                for &id in &ids {
+                    if budget > 0 && !graph.sequences.contains_key(&id) && !next.contains(&id) {
+                        next.push(id);
+                        budget -= 1;
+                    }
+                }
+                graph.edges.insert(id, ids);
+            }
+            if next.is_empty() {
+                break;
+            }
+            let mut fetched = self.fetch_many(&next)?;
+            next.retain(|id| fetched.contains_key(id));
+            for id in &next {
+                graph
+                    .sequences
+                    .insert(*id, fetched.remove(id).expect("fetched sequence"));
+            }
+            frontier = next;
+        }
+        Ok(graph)
+    }
+
+    /// Fetch a sequence like [`Client::fetch`], but using the OEIS internal
+    /// text format (`fmt=text`) instead of JSON.
+    pub fn fetch_text(&self, id: u64) -> Result<OeisSequence, FetchError> {
+        let query = Query::new().id(id).to_string();
+        let text = self.get_text(SEARCH_URL, &[("q", &query), ("fmt", "text")])?;
+        let entry = parse_internal(&text)?
+            .into_iter()
+            .next()
+            .ok_or(FetchError::NotFound(id))?;
+        check_number(id, entry.number)?;
+        self.sequence(entry)
+    }
+
+    /// Fetch the `n` most recently added sequences, newest first.
+    pub fn fetch_recent_additions(&self, n: usize) -> Result<Vec<OeisSequence>, FetchError> {
+        let query = Query::new().keyword(Keyword::New).sort(Sort::Created);
+        self.search(&query).take(n).collect()
+    }
+
+    /// Fetch the `n` sequences matching `query` that were most recently
+    /// modified, latest first. With [`Query::ids`], this tells which of the
+    /// given sequences changed since they were last seen.
+    pub fn fetch_recently_modified(
+        &self,
+        query: &Query,
+        n: usize,
+    ) -> Result<Vec<OeisSequence>, FetchError> {
+        let query = query.clone().sort(Sort::Modified);
+        self.search(&query).take(n).collect()
+    }
+
+    /// Number of sequences matching `query`, without parsing the matching
+    /// entries. The JSON API does not report it, so it is read from the
+    /// header of the results in the internal format.
+    ///
+    /// Fails with [`FetchError::MissingCount`] if the response has neither
+    /// the header nor says that there are no results.
+    pub fn count(&self, query: &Query) -> Result<usize, FetchError> {
+        let q = query.to_string();
+        let text = self.get_text(SEARCH_URL, &[("q", &q), ("fmt", "text")])?;
+        // e.g. "Showing 1-10 of 1234"
+        let count = text
+            .lines()
+            .filter_map(|line| line.strip_prefix("Showing "))
+            .find_map(|line| line.rsplit_once(" of "))
+            .and_then(|(_, n)| n.trim().parse().ok());
+        match count {
+            Some(n) => Ok(n),
+            None if text.contains("No results") => Ok(0),
+            None => Err(FetchError::MissingCount),
+        }
+    }
+
+    /// Pick a random sequence among those matching `query` (e.g. with
+    /// [`Query::keyword`]), excluding sequences with one of the rejected
+    /// keywords. Unlike [`Client::fetch_random`], this takes two requests
+    /// whatever the query.
+    pub fn fetch_random_matching(&self, query: &Query) -> Result<OeisSequence, FetchError> {
+        let query = REJECTED_KEYWORDS
+            .iter()
+            .fold(query.clone(), |query, kw| query.without_keyword(kw.clone()));
+        let count = self.count(&query)?;
+        if count == 0 {
+            return Err(FetchError::NoResults(query.to_string()));
+        }
+        let offset = rand::rng().random_range(0..count);
+        let entry = self
+            .search_page(&query, offset)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| FetchError::NoResults(query.to_string()))?;
+        self.sequence(entry)
+    }
+
+    /// Search for sequences containing the given terms, in order (e.g.
+    /// `1, 1, 2, 3, 5`). Returns the first page of matches. Terms given by
+    /// users can be read with [`terms::read_terms`](crate::terms::read_terms).
+    pub fn search_by_terms(&self, terms: &[BigInt]) -> Result<Vec<OeisSequence>, FetchError> {
+        let entries = self.search_page(&Query::new().terms(terms), 0)?;
+        entries.into_iter().map(|e| self.sequence(e)).collect()
+    }
+
+    /// Search for sequences whose author field contains `author` (e.g.
+    /// `N. J. A. Sloane`), walking through all pages of results.
+    pub fn search_by_author(&self, author: &str) -> SearchResults {
+        self.search(&Query::new().author(author))
+    }
+
+    /// Search the OEIS, walking through all pages of results.
+    pub fn search(&self, query: &Query) -> SearchResults {
+        SearchResults {
+            client: self.clone(),
+            query: query.clone(),
+            start: 0,
+            page: Vec::new().into_iter(),
+            total: None,
+            done: false,
+        }
+    }
+
+    /// Convert a fetched entry, recording its provenance.
+    fn sequence(&self, entry: OeisEntry) -> Result<OeisSequence, FetchError> {
+        Ok(OeisSequence::try_from(entry)?.fetched_at(self.clock.now()))
+    }
+
+    /// Fetch a single page of results for a search query, starting at result
+    /// number `start`.
+    fn search_page(&self, query: &Query, start: usize) -> Result<Vec<OeisEntry>, FetchError> {
+        Ok(self.search_page_counted(query, start)?.0)
+    }
+
+    /// Like [`Client::search_page`], also returning the total number of
+    /// matches if the response includes it.
+    fn search_page_counted(
+        &self,
+        query: &Query,
+        start: usize,
+    ) -> Result<(Vec<OeisEntry>, Option<usize>), FetchError> {
+        let q = query.to_string();
+        let start = start.to_string();
+        let mut params = vec![("q", q.as_str()), ("fmt", "json"), ("start", &start)];
+        if let Some(sort) = query.sort_order() {
+            params.push(("sort", sort.as_str()));
+        }
+        let json = self.get_text(SEARCH_URL, &params)?;
+        let page = SearchPage::from_json(&json)?;
+        Ok(page.into_parts())
+    }
+
+    /// The largest A-number in the OEIS, taken from the most recently created
+    /// sequences. The result is cached for a day, and shared by the clones
+    /// of the client.
+    pub fn max_sequence_id(&self) -> Result<u64, FetchError> {
+        let now = self.clock.now();
+        if let Some((id, discovered_at)) = *self.max_id.lock().unwrap()
+            && now < discovered_at + MAX_SEQUENCE_ID_TTL
+        {
+            return Ok(id);
+        }
+        let query = Query::new().keyword(Keyword::New).sort(Sort::Created);
+        let id = self
+            .search_page(&query, 0)?
+            .iter()
+            .map(|entry| entry.number)
+            .max()
+            .ok_or_else(|| FetchError::NoResults(query.to_string()))?;
+        *self.max_id.lock().unwrap() = Some((id, now));
+        Ok(id)
+    }
+
+    /// Fetch a random sequence from the OEIS, excluding sequences with
+    /// one of the rejected keywords.
+    ///
+    /// Random A-numbers are tried until an acceptable sequence is found, at
+    /// most [`MAX_RANDOM_ATTEMPTS`] times.
+    pub fn fetch_random(&self) -> Result<OeisSequence, SelectionError> {
+        self.fetch_random_with_stats().map(|(seq, _)| seq)
+    }
+
+    /// Fetch a random sequence like [`Client::fetch_random`], also returning
+    /// the attempts made to find it.
+    pub fn fetch_random_with_stats(
+        &self,
+    ) -> Result<(OeisSequence, SelectionStats), SelectionError> {
+        let mut stats = SelectionStats::default();
+        let result = self.pick_random(&mut stats);
+        *self
+            .random_attempts
+            .lock()
+            .unwrap()
+            .entry(stats.attempts)
+            .or_default() += 1;
+        result.map(|seq| (seq, stats))
+    }
+
+    /// Distribution of the number of attempts made by
+    /// [`Client::fetch_random`], as the number of calls by number of
+    /// attempts. It is shared by the clones of the client.
+    pub fn random_attempts(&self) -> BTreeMap<u32, u64> {
+        self.random_attempts.lock().unwrap().clone()
+    }
+
+    /// Try random A-numbers until an acceptable sequence is found, counting
+    /// the attempts in `stats`.
+    fn pick_random(&self, stats: &mut SelectionStats) -> Result<OeisSequence, SelectionError> {
+        let max_id = self.max_sequence_id().unwrap_or(MAX_SEQUENCE_ID);
+        let mut rng = rand::rng();
+        while stats.attempts < MAX_RANDOM_ATTEMPTS {
+            stats.attempts += 1;
+            let id = rng.random_range(1..=max_id);
+            let seq = match self.fetch(id) {
+                Ok(seq) => seq,
+                Err(FetchError::NotFound(_) | FetchError::Mismatch { .. }) => {
+                    stats.not_found += 1;
+                    continue;
+                }
+                Err(FetchError::InvalidEntry(_)) => {
+                    stats.invalid += 1;
+                    continue;
+                }
+                Err(e) => return Err(e.into()),
+            };
+            if seq.keyword_set().intersects(REJECTED_KEYWORD_SET) {
+                stats.rejected += 1;
+                continue;
+            }
+            return Ok(seq);
+        }
+        Err(SelectionError::Exhausted(*stats))
+    }
+
+    /// Run `f`, retrying on transient errors according to the retry policy.
+    fn with_retry<T>(&self, mut f: impl FnMut() -> Result<T, FetchError>) -> Result<T, FetchError> {
+        let mut attempt = 1;
+        loop {
+            let e = match f() {
+                Err(e) if e.is_transient() && attempt < self.retry.max_attempts => e,
+                result => return result,
+            };
+            let delay = match e {
+                FetchError::RateLimited {
+                    retry_after: Some(delay),
+                } if delay > self.retry.max_backoff => return Err(e),
+                FetchError::RateLimited {
+                    retry_after: Some(delay),
+                } => delay,
+                _ => self.retry.backoff(attempt),
+            };
+            self.clock.sleep(delay);
+            attempt += 1;
+        }
+    }
+
+    /// GET a text resource, going through the HTTP cache if there is one.
+    fn get_text(&self, url: &str, query: &[(&str, &str)]) -> Result<String, FetchError> {
+        self.with_retry(|| self.get_text_once(url, query))
+    }
+
+    fn get_text_once(&self, url: &str, query: &[(&str, &str)]) -> Result<String, FetchError> {
+        let Some(cache) = &self.http_cache else {
+            return read_text(self.get(self.request(url, query))?);
         };
-        if seq.keyword.iter().any(|kw| REJECTED_KEYWORDS.contains(kw)) {
-            continue;
+        let key = HttpCache::key(url, query);
+        let cached = cache.load(&key);
+        if let Some(cached) = &cached
+            && cached.fresh
+        {
+            return Ok(cached.body.clone());
+        }
+        let mut req = self.request(url, query);
+        if let Some(cached) = &cached {
+            if let Some(etag) = &cached.etag {
+                req = req.header("If-None-Match", etag);
+            }
+            if let Some(last_modified) = &cached.last_modified {
+                req = req.header("If-Modified-Since", last_modified);
+            }
+        }
+        let response = self.transport.send(req)?;
+        if response.status == 304
+            && let Some(cached) = cached
+        {
+            cache.touch(&key, &cached);
+            return Ok(cached.body);
+        }
+        if !response.is_success() {
+            return Err(self.status_error(&response));
+        }
+        let etag = response.header("etag").map(str::to_owned);
+        let last_modified = response.header("last-modified").map(str::to_owned);
+        let body = read_text(response)?;
+        cache.store(&key, &body, etag.as_deref(), last_modified.as_deref());
+        Ok(body)
+    }
+
+    fn request(&self, url: &str, query: &[(&str, &str)]) -> Request {
+        let mut req = Request::get(url).header("User-Agent", &self.user_agent);
+        for (name, value) in query {
+            req = req.query(name, value);
         }
-        return seq;
+        req
+    }
+
+    /// Send `req`, failing unless the response has a success status.
+    fn get(&self, req: Request) -> Result<Response, FetchError> {
+        let response = self.transport.send(req)?;
+        if !response.is_success() {
+            return Err(self.status_error(&response));
+        }
+        Ok(response)
+    }
+
+    /// The error for a response with an unsuccessful status.
+    fn status_error(&self, response: &Response) -> FetchError {
+        match response.status {
+            429 | 503 => FetchError::RateLimited {
+                retry_after: response
+                    .header("retry-after")
+                    .and_then(|value| self.parse_retry_after(value)),
+            },
+            status => FetchError::Status(status),
+        }
+    }
+
+    /// Parse a `Retry-After` header, either a number of seconds or a date.
+    fn parse_retry_after(&self, value: &str) -> Option<Duration> {
+        let value = value.trim();
+        if let Ok(secs) = value.parse() {
+            return Some(Duration::from_secs(secs));
+        }
+        let date = httpdate::parse_http_date(value).ok()?;
+        Some(date.duration_since(self.clock.now()).unwrap_or_default())
+    }
+
+    /// GET a resource to be streamed, e.g. the bulk dumps. Only the initial
+    /// request is retried.
+    pub(crate) fn get_reader(&self, url: &str) -> Result<impl Read + use<>, FetchError> {
+        let response = self.with_retry(|| self.get(self.request(url, &[])))?;
+        Ok(response.body)
+    }
+
+    /// GET a binary resource. These are not cached.
+    fn get_bytes(&self, url: &str, query: &[(&str, &str)]) -> Result<Vec<u8>, FetchError> {
+        self.with_retry(|| {
+            let mut body = Vec::new();
+            self.get(self.request(url, query))?
+                .body
+                .read_to_end(&mut body)?;
+            Ok(body)
+        })
+    }
+}
+
+/// Read the body of a text response. All text resources of the OEIS are
+/// plain text or JSON, so an HTML page is an error page, e.g. during
+/// maintenance.
+fn read_text(mut response: Response) -> Result<String, FetchError> {
+    let mut body = String::new();
+    response.body.read_to_string(&mut body)?;
+    if is_html(&body) {
+        return Err(FetchError::ServiceUnavailable);
+    }
+    Ok(body)
+}
+
+/// Fail with [`FetchError::Mismatch`] unless the OEIS answered with the
+/// `requested` sequence.
+pub(crate) fn check_number(requested: u64, got: u64) -> Result<(), FetchError> {
+    if got != requested {
+        return Err(FetchError::Mismatch { requested, got });
+    }
+    Ok(())
+}
+
+/// Whether `body` looks like an HTML document.
+pub(crate) fn is_html(body: &str) -> bool {
+    let start = body.trim_start().get(..5).unwrap_or("");
+    start.eq_ignore_ascii_case("<!doc") || start.eq_ignore_ascii_case("<html")
+}
+
+/// Iterator over the results of a search query.
+///
+/// The OEIS returns results in pages of 10; further pages are fetched on
+/// demand as the iterator advances. Iteration stops after the first failed
+/// request; malformed entries are returned as errors and skipped.
+#[derive(Debug)]
+pub struct SearchResults {
+    client: Client,
+    query: Query,
+    start: usize,
+    page: std::vec::IntoIter<OeisEntry>,
+    total: Option<usize>,
+    done: bool,
+}
+
+impl SearchResults {
+    /// Total number of matches for the query, or `None` if it cannot be
+    /// found.
+    ///
+    /// The JSON API does not report it directly, so unless the last page has
+    /// been fetched, it is requested with [`Client::count`]. This can be done
+    /// before iterating.
+    pub fn total(&mut self) -> Option<usize> {
+        if self.total.is_none() && !self.done {
+            self.total = self.client.count(&self.query).ok();
+        }
+        self.total
+    }
+
+    fn fetch_next_page(&mut self) -> Result<(), FetchError> {
+        let (entries, count) = self.client.search_page_counted(&self.query, self.start)?;
+        if count.is_some() {
+            self.total = count;
+        }
+        if entries.len() < PAGE_SIZE {
+            self.done = true;
+            self.total = Some(self.start + entries.len());
+        }
+        self.start += entries.len();
+        self.page = entries.into_iter();
+        Ok(())
+    }
+}
+
+impl Iterator for SearchResults {
+    type Item = Result<OeisSequence, FetchError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(entry) = self.page.next() {
+                return Some(self.client.sequence(entry));
+            }
+            if self.done {
+                return None;
+            }
+            if let Err(e) = self.fetch_next_page() {
+                self.done = true;
+                return Some(Err(e));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use std::collections::VecDeque;
+    use std::io::Cursor;
+
+    const BFILE: &str = "0 0\n1 1\n2 1\n3 2\n";
+
+    /// Status, headers and body of a scripted response.
+    type ScriptedResponse = (u16, Vec<(String, String)>, &'static str);
+
+    /// A transport answering requests with scripted responses, in order.
+    #[derive(Debug, Default)]
+    struct Scripted {
+        responses: Mutex<VecDeque<ScriptedResponse>>,
+        requests: Mutex<Vec<Request>>,
+    }
+
+    impl Scripted {
+        fn respond(self, status: u16, headers: &[(&str, &str)], body: &'static str) -> Self {
+            let headers = headers
+                .iter()
+                .map(|(n, v)| ((*n).to_owned(), (*v).to_owned()))
+                .collect();
+            self.responses
+                .lock()
+                .unwrap()
+                .push_back((status, headers, body));
+            self
+        }
+
+        fn requests(&self) -> usize {
+            self.requests.lock().unwrap().len()
+        }
+    }
+
+    impl HttpTransport for Scripted {
+        fn send(&self, request: Request) -> Result<Response, TransportError> {
+            self.requests.lock().unwrap().push(request);
+            let (status, headers, body) = self
+                .responses
+                .lock()
+                .unwrap()
+                .pop_front()
+                .expect("unexpected request");
+            Ok(Response {
+                status,
+                headers,
+                body: Box::new(Cursor::new(body.as_bytes())),
+            })
+        }
+    }
+
+    const POLICY: RetryPolicy = RetryPolicy {
+        max_attempts: 4,
+        initial_backoff: Duration::from_secs(1),
+        max_backoff: Duration::from_secs(30),
+    };
+
+    /// A client sending requests to `transport`, with a mock clock.
+    fn client(transport: &Arc<Scripted>, policy: RetryPolicy) -> (Client, Arc<MockClock>) {
+        let clock = Arc::new(MockClock::default());
+        let client = Client::new()
+            .with_transport(transport.clone())
+            .with_clock(clock.clone())
+            .with_retry_policy(policy);
+        (client, clock)
+    }
+
+    fn elapsed(clock: &MockClock) -> Duration {
+        clock.now().duration_since(SystemTime::UNIX_EPOCH).unwrap()
+    }
+
+    #[test]
+    fn backoff_bounds() {
+        for _ in 0..100 {
+            assert!(POLICY.backoff(1) <= Duration::from_secs(1));
+            assert!(POLICY.backoff(2) <= Duration::from_secs(2));
+            assert!(POLICY.backoff(3) <= Duration::from_secs(4));
+            assert!(POLICY.backoff(10) <= POLICY.max_backoff);
+        }
+    }
+
+    #[test]
+    fn retries_transient_errors() {
+        let transport = Arc::new(
+            Scripted::default()
+                .respond(500, &[], "")
+                .respond(502, &[], "")
+                .respond(200, &[], BFILE),
+        );
+        let (client, clock) = client(&transport, POLICY);
+        assert_eq!(client.fetch_bfile(45).unwrap().len(), 4);
+        assert_eq!(transport.requests(), 3);
+        // Backoffs of at most 1 and 2 seconds.
+        assert!(elapsed(&clock) <= Duration::from_secs(3));
+    }
+
+    #[test]
+    fn gives_up_after_max_attempts() {
+        let mut transport = Scripted::default();
+        for _ in 0..POLICY.max_attempts {
+            transport = transport.respond(500, &[], "");
+        }
+        let transport = Arc::new(transport);
+        let (client, clock) = client(&transport, POLICY);
+        assert!(matches!(
+            client.fetch_bfile(45),
+            Err(FetchError::Status(500))
+        ));
+        assert_eq!(transport.requests(), 4);
+        // Backoffs of at most 1, 2 and 4 seconds.
+        assert!(elapsed(&clock) <= Duration::from_secs(7));
+    }
+
+    #[test]
+    fn does_not_retry_permanent_errors() {
+        let transport = Arc::new(Scripted::default().respond(404, &[], ""));
+        let (client, clock) = client(&transport, POLICY);
+        assert!(matches!(
+            client.fetch_bfile(45),
+            Err(FetchError::Status(404))
+        ));
+        assert_eq!(transport.requests(), 1);
+        assert_eq!(elapsed(&clock), Duration::ZERO);
+    }
+
+    #[test]
+    fn no_retry_policy() {
+        let transport = Arc::new(Scripted::default().respond(500, &[], ""));
+        let (client, _) = client(&transport, RetryPolicy::none());
+        assert!(client.fetch_bfile(45).is_err());
+        assert_eq!(transport.requests(), 1);
+    }
+
+    #[test]
+    fn waits_for_retry_after() {
+        let transport = Arc::new(
+            Scripted::default()
+                .respond(429, &[("Retry-After", "5")], "")
+                .respond(200, &[], BFILE),
+        );
+        let (client, clock) = client(&transport, POLICY);
+        assert!(client.fetch_bfile(45).is_ok());
+        assert_eq!(transport.requests(), 2);
+        assert_eq!(elapsed(&clock), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn gives_up_when_retry_after_is_too_long() {
+        let transport = Arc::new(Scripted::default().respond(503, &[("Retry-After", "3600")], ""));
+        let (client, clock) = client(&transport, POLICY);
+        assert!(matches!(
+            client.fetch_bfile(45),
+            Err(FetchError::RateLimited {
+                retry_after: Some(delay)
+            }) if delay == Duration::from_secs(3600)
+        ));
+        assert_eq!(transport.requests(), 1);
+        assert_eq!(elapsed(&clock), Duration::ZERO);
+    }
+
+    #[test]
+    fn revalidates_stale_cache() {
+        let transport = Arc::new(
+            Scripted::default()
+                .respond(200, &[("ETag", "\"v1\"")], BFILE)
+                .respond(304, &[], ""),
+        );
+        let (client, clock) = client(&transport, POLICY);
+        let dir = std::env::temp_dir().join(format!(
+            "oeis_bot-{}-revalidates_stale_cache",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        let ttl = Duration::from_secs(60);
+        let client = client.with_http_cache(HttpCache::new(&dir, ttl).with_clock(clock.clone()));
+
+        assert_eq!(client.fetch_bfile(45).unwrap().len(), 4);
+        clock.advance(ttl / 2);
+        assert_eq!(client.fetch_bfile(45).unwrap().len(), 4);
+        assert_eq!(transport.requests(), 1);
+
+        clock.advance(ttl);
+        assert_eq!(client.fetch_bfile(45).unwrap().len(), 4);
+        assert_eq!(transport.requests(), 2);
+        let request = &transport.requests.lock().unwrap()[1];
+        assert!(
+            request
+                .headers
+                .iter()
+                .any(|(n, v)| n == "If-None-Match" && v == "\"v1\"")
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn fetch_text_rejects_other_sequence() {
+        let transport = Arc::new(Scripted::default().respond(
+            200,
+            &[],
+            "%I A000032\n%S A000032 2,1,3,4\n%N A000032 Lucas numbers.\n%O A000032 0,1\n",
+        ));
+        let (client, _) = client(&transport, POLICY);
+        assert!(matches!(
+            client.fetch_text(45),
+            Err(FetchError::Mismatch {
+                requested: 45,
+                got: 32
+            })
+        ));
     }
 }
@@ -0,0 +1,435 @@
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::io::Read;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Settings applied to all outgoing HTTP requests.
+#[derive(Debug, Clone)]
+pub struct HttpConfig {
+    pub timeouts: Timeouts,
+    /// URL of the proxy to send requests through (e.g.
+    /// `socks5://127.0.0.1:9050`), or `None` to connect directly.
+    pub proxy: Option<String>,
+}
+
+impl Default for HttpConfig {
+    /// Default timeouts, and the proxy given by the `ALL_PROXY`,
+    /// `HTTPS_PROXY` or `HTTP_PROXY` environment variables if any.
+    fn default() -> Self {
+        let proxy = [
+            "ALL_PROXY",
+            "all_proxy",
+            "HTTPS_PROXY",
+            "https_proxy",
+            "HTTP_PROXY",
+            "http_proxy",
+        ]
+        .into_iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|url| !url.is_empty());
+        Self {
+            timeouts: Timeouts::default(),
+            proxy,
+        }
+    }
+}
+
+/// Timeouts applied to outgoing HTTP requests, so that a hung connection
+/// cannot stall the bot indefinitely. `None` disables a timeout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timeouts {
+    /// Maximum time to establish a connection.
+    pub connect: Option<Duration>,
+    /// Maximum time to wait for the response headers, and then again for
+    /// the response body.
+    pub read: Option<Duration>,
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Self {
+            connect: Some(Duration::from_secs(10)),
+            read: Some(Duration::from_secs(60)),
+        }
+    }
+}
+
+/// Sends HTTP requests on behalf of [`Client`](crate::fetch::Client) and
+/// [`Mastodon`](crate::mastodon::Mastodon).
+///
+/// Implementations are provided for ureq ([`UreqTransport`], with the
+/// `ureq` feature, enabled by default) and for the blocking reqwest client
+/// ([`ReqwestTransport`], with the `reqwest` feature). Implementations
+/// follow redirects and decompress gzip-encoded responses, and return
+/// responses with an error status as `Ok`.
+pub trait HttpTransport: fmt::Debug + Send + Sync {
+    fn send(&self, request: Request) -> Result<Response, TransportError>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    Get,
+    Post,
+    Patch,
+}
+
+/// An outgoing HTTP request.
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub method: Method,
+    pub url: String,
+    /// Query parameters, appended to the URL by the transport.
+    pub query: Vec<(String, String)>,
+    pub headers: Vec<(String, String)>,
+    /// Fields sent as a form-encoded body. Ignored for GET requests.
+    pub form: Vec<(String, String)>,
+    /// Raw body, sent instead of the form if set. Ignored for GET requests.
+    pub body: Option<Vec<u8>>,
+}
+
+impl Request {
+    pub fn new(method: Method, url: &str) -> Self {
+        Self {
+            method,
+            url: url.to_owned(),
+            query: Vec::new(),
+            headers: Vec::new(),
+            form: Vec::new(),
+            body: None,
+        }
+    }
+
+    pub fn get(url: &str) -> Self {
+        Self::new(Method::Get, url)
+    }
+
+    pub fn post(url: &str) -> Self {
+        Self::new(Method::Post, url)
+    }
+
+    pub fn patch(url: &str) -> Self {
+        Self::new(Method::Patch, url)
+    }
+
+    pub fn query(mut self, name: &str, value: &str) -> Self {
+        self.query.push((name.to_owned(), value.to_owned()));
+        self
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_owned(), value.to_owned()));
+        self
+    }
+
+    pub fn form(mut self, name: &str, value: &str) -> Self {
+        self.form.push((name.to_owned(), value.to_owned()));
+        self
+    }
+
+    pub fn body(mut self, body: Vec<u8>) -> Self {
+        self.body = Some(body);
+        self
+    }
+}
+
+/// A response to a [`Request`], with its body yet to be read.
+pub struct Response {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Box<dyn Read + Send>,
+}
+
+impl Response {
+    /// Value of the header `name`, compared case-insensitively.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Whether the status is in the 2xx range.
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+impl fmt::Debug for Response {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Response")
+            .field("status", &self.status)
+            .field("headers", &self.headers)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A request that could not be sent, or whose response could not be
+/// received.
+#[derive(Debug)]
+pub struct TransportError {
+    kind: TransportErrorKind,
+    source: Box<dyn Error + Send + Sync>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransportErrorKind {
+    /// The connection could not be established.
+    Connect,
+    Timeout,
+    /// The connection failed while the request was in progress.
+    Io,
+    /// The transport is misconfigured, e.g. with an invalid proxy URL.
+    Config,
+    Other,
+}
+
+impl TransportError {
+    pub fn new(kind: TransportErrorKind, source: impl Into<Box<dyn Error + Send + Sync>>) -> Self {
+        Self {
+            kind,
+            source: source.into(),
+        }
+    }
+
+    pub fn kind(&self) -> TransportErrorKind {
+        self.kind
+    }
+
+    /// Whether the request may succeed if retried.
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self.kind,
+            TransportErrorKind::Connect | TransportErrorKind::Timeout | TransportErrorKind::Io
+        )
+    }
+}
+
+impl fmt::Display for TransportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+impl Error for TransportError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&*self.source)
+    }
+}
+
+/// Build a transport with the given settings, using ureq if the `ureq`
+/// feature is enabled and reqwest otherwise.
+#[cfg(feature = "ureq")]
+pub fn transport(config: &HttpConfig) -> Result<Arc<dyn HttpTransport>, TransportError> {
+    Ok(Arc::new(UreqTransport::new(config)?))
+}
+
+/// Build a transport with the given settings, using ureq if the `ureq`
+/// feature is enabled and reqwest otherwise.
+#[cfg(all(feature = "reqwest", not(feature = "ureq")))]
+pub fn transport(config: &HttpConfig) -> Result<Arc<dyn HttpTransport>, TransportError> {
+    Ok(Arc::new(ReqwestTransport::new(config)?))
+}
+
+/// Build a transport with the given settings, using ureq if the `ureq`
+/// feature is enabled and reqwest otherwise.
+#[cfg(not(any(feature = "ureq", feature = "reqwest")))]
+pub fn transport(_config: &HttpConfig) -> Result<Arc<dyn HttpTransport>, TransportError> {
+    Err(TransportError::new(
+        TransportErrorKind::Config,
+        "no HTTP transport available, enable the `ureq` or `reqwest` feature",
+    ))
+}
+
+/// The transport used unless another one is given: default settings, but
+/// connecting directly if the proxy from the environment is invalid. If no
+/// transport is available, every request fails.
+pub(crate) fn default_transport() -> Arc<dyn HttpTransport> {
+    let config = HttpConfig::default();
+    transport(&config)
+        .or_else(|_| {
+            transport(&HttpConfig {
+                proxy: None,
+                ..config
+            })
+        })
+        .unwrap_or_else(|e| Arc::new(Unavailable(e.to_string())))
+}
+
+#[derive(Debug)]
+struct Unavailable(String);
+
+impl HttpTransport for Unavailable {
+    fn send(&self, _request: Request) -> Result<Response, TransportError> {
+        Err(TransportError::new(
+            TransportErrorKind::Config,
+            self.0.clone(),
+        ))
+    }
+}
+
+/// Transport based on ureq. Connections are kept alive and reused across
+/// requests made with the transport and its clones. SOCKS proxies are
+/// supported.
+#[cfg(feature = "ureq")]
+#[derive(Debug, Clone)]
+pub struct UreqTransport {
+    agent: ureq::Agent,
+}
+
+#[cfg(feature = "ureq")]
+impl UreqTransport {
+    pub fn new(config: &HttpConfig) -> Result<Self, TransportError> {
+        let proxy = config.proxy.as_deref().map(ureq::Proxy::new).transpose()?;
+        let agent = ureq::Agent::config_builder()
+            .http_status_as_error(false)
+            .timeout_connect(config.timeouts.connect)
+            .timeout_recv_response(config.timeouts.read)
+            .timeout_recv_body(config.timeouts.read)
+            .proxy(proxy)
+            .build()
+            .new_agent();
+        Ok(Self { agent })
+    }
+}
+
+#[cfg(feature = "ureq")]
+impl HttpTransport for UreqTransport {
+    fn send(&self, request: Request) -> Result<Response, TransportError> {
+        fn prepare<B>(
+            mut builder: ureq::RequestBuilder<B>,
+            request: &Request,
+        ) -> ureq::RequestBuilder<B> {
+            for (name, value) in &request.headers {
+                builder = builder.header(name, value);
+            }
+            builder.query_pairs(request.query.iter().map(|(k, v)| (k.as_str(), v.as_str())))
+        }
+        let builder = match request.method {
+            Method::Get => {
+                return Ok(prepare(self.agent.get(&request.url), &request)
+                    .call()?
+                    .into());
+            }
+            Method::Post => prepare(self.agent.post(&request.url), &request),
+            Method::Patch => prepare(self.agent.patch(&request.url), &request),
+        };
+        let response = match &request.body {
+            Some(body) => builder.send(body)?,
+            None => {
+                builder.send_form(request.form.iter().map(|(k, v)| (k.as_str(), v.as_str())))?
+            }
+        };
+        Ok(response.into())
+    }
+}
+
+#[cfg(feature = "ureq")]
+impl From<ureq::http::Response<ureq::Body>> for Response {
+    fn from(response: ureq::http::Response<ureq::Body>) -> Self {
+        let headers = response
+            .headers()
+            .iter()
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_owned())))
+            .collect();
+        Response {
+            status: response.status().as_u16(),
+            headers,
+            body: Box::new(response.into_body().into_reader()),
+        }
+    }
+}
+
+#[cfg(feature = "ureq")]
+impl From<ureq::Error> for TransportError {
+    fn from(e: ureq::Error) -> Self {
+        let kind = match e {
+            ureq::Error::ConnectionFailed => TransportErrorKind::Connect,
+            ureq::Error::Timeout(_) => TransportErrorKind::Timeout,
+            ureq::Error::Io(_) | ureq::Error::BodyStalled => TransportErrorKind::Io,
+            ureq::Error::InvalidProxyUrl | ureq::Error::BadUri(_) => TransportErrorKind::Config,
+            _ => TransportErrorKind::Other,
+        };
+        Self::new(kind, e)
+    }
+}
+
+/// Transport based on the blocking reqwest client. Connections are kept
+/// alive and reused across requests made with the transport and its clones.
+/// SOCKS proxies are supported.
+#[cfg(feature = "reqwest")]
+#[derive(Debug, Clone)]
+pub struct ReqwestTransport {
+    client: reqwest::blocking::Client,
+}
+
+#[cfg(feature = "reqwest")]
+impl ReqwestTransport {
+    /// The read timeout applies to the whole request, as the blocking
+    /// client has no separate timeout for the response body.
+    pub fn new(config: &HttpConfig) -> Result<Self, TransportError> {
+        let mut builder = reqwest::blocking::Client::builder()
+            .connect_timeout(config.timeouts.connect)
+            .timeout(config.timeouts.read);
+        if let Some(proxy) = &config.proxy {
+            builder = builder.proxy(reqwest::Proxy::all(proxy)?);
+        }
+        Ok(Self {
+            client: builder.build()?,
+        })
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl HttpTransport for ReqwestTransport {
+    fn send(&self, request: Request) -> Result<Response, TransportError> {
+        let method = match request.method {
+            Method::Get => reqwest::Method::GET,
+            Method::Post => reqwest::Method::POST,
+            Method::Patch => reqwest::Method::PATCH,
+        };
+        let mut builder = self
+            .client
+            .request(method, &request.url)
+            .query(&request.query);
+        for (name, value) in &request.headers {
+            builder = builder.header(name, value);
+        }
+        if request.method != Method::Get {
+            builder = match request.body {
+                Some(body) => builder.body(body),
+                None => builder.form(&request.form),
+            };
+        }
+        let response = builder.send()?;
+        let headers = response
+            .headers()
+            .iter()
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_owned())))
+            .collect();
+        Ok(Response {
+            status: response.status().as_u16(),
+            headers,
+            body: Box::new(response),
+        })
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl From<reqwest::Error> for TransportError {
+    fn from(e: reqwest::Error) -> Self {
+        let kind = if e.is_connect() {
+            TransportErrorKind::Connect
+        } else if e.is_timeout() {
+            TransportErrorKind::Timeout
+        } else if e.is_body() || e.is_decode() {
+            TransportErrorKind::Io
+        } else if e.is_builder() {
+            TransportErrorKind::Config
+        } else {
+            TransportErrorKind::Other
+        };
+        Self::new(kind, e)
+    }
+}
@@ -0,0 +1,293 @@
+use crate::oeis::{ANumber, OeisEntry, OeisSequence};
+use std::fmt;
+
+#[derive(Debug, Clone)]
+pub struct ParseInternalError {
+    /// Line number (starting from 1) of the malformed line.
+    pub line: usize,
+    /// Content of the malformed line.
+    pub content: String,
+}
+
+impl fmt::Display for ParseInternalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid line {} in OEIS internal format: {:?}",
+            self.line, self.content
+        )
+    }
+}
+
+impl std::error::Error for ParseInternalError {}
+
+/// Tags of the fields read by [`parse_internal`].
+const TAGS: &[&str] = &[
+    "I", "S", "T", "U", "N", "C", "D", "H", "F", "e", "p", "t", "o", "Y", "K", "O", "A", "E",
+];
+
+/// Parse entries in the OEIS internal format, as returned with `fmt=text`.
+///
+/// Each line has the form `%X A000045 content`, where `X` identifies the
+/// field. Lines that do not start with `%` (headers, blank lines) and lines
+/// with an unknown field are skipped. The internal format does not include the number of references
+/// nor the creation date, which are left empty. See the
+/// [documentation](https://oeis.org/eishelp1.html) for details.
+pub fn parse_internal(s: &str) -> Result<Vec<OeisEntry>, ParseInternalError> {
+    let mut entries: Vec<OeisEntry> = Vec::new();
+    for (i, line) in s.lines().enumerate() {
+        if !line.starts_with('%') {
+            continue;
+        }
+        let err = || ParseInternalError {
+            line: i + 1,
+            content: line.to_owned(),
+        };
+        let mut parts = line.splitn(3, ' ');
+        let tag = parts
+            .next()
+            .and_then(|t| t.strip_prefix('%'))
+            .ok_or_else(err)?;
+        let number: u64 = parts
+            .next()
+            .and_then(|a| a.strip_prefix('A'))
+            .and_then(|n| n.parse().ok())
+            .ok_or_else(err)?;
+        let content = parts.next().unwrap_or("").trim_end().to_owned();
+        if !TAGS.contains(&tag) {
+            continue;
+        }
+
+        if entries.last().is_none_or(|e| e.number != number) {
+            entries.push(OeisEntry {
+                number,
+                ..Default::default()
+            });
+        }
+        let entry = entries.last_mut().expect("entry was just pushed");
+        match tag {
+            "I" => parse_identification(entry, &content),
+            "S" | "T" | "U" => entry.data.push_str(&content),
+            "N" => append(&mut entry.name, &content),
+            "C" => entry.comment.push(content),
+            "D" => entry.reference.push(content),
+            "H" => entry.link.push(content),
+            "F" => entry.formula.push(content),
+            "e" => entry.example.push(content),
+            "p" => entry.maple.push(content),
+            "t" => entry.mathematica.push(content),
+            "o" => entry.program.push(content),
+            "Y" => entry.xref.push(content),
+            "K" => entry.keyword = content,
+            "O" => entry.offset = content,
+            "A" => append(&mut entry.author, &content),
+            "E" => entry.ext.push(content),
+            _ => unreachable!("unknown tags are skipped"),
+        }
+    }
+    Ok(entries)
+}
+
+/// Maximum length of the content of the `%S`, `%T` and `%U` lines written
+/// by [`write_internal`].
+const DATA_LINE_LEN: usize = 69;
+
+/// Write `seq` in the OEIS internal format, as parsed by
+/// [`parse_internal`].
+///
+/// Fields are written in the order of the OEIS, and the terms are wrapped
+/// over `%S`, `%T` and `%U` lines. Fields that the internal format does not
+/// include (number of references, creation date) are left out.
+pub fn write_internal(seq: &OeisSequence) -> String {
+    let number = ANumber(seq.number);
+    let mut out = String::new();
+    let mut line = |tag: char, content: &str| {
+        let line = format!("%{tag} {number} {content}");
+        out.push_str(line.trim_end());
+        out.push('\n');
+    };
+
+    let mut identification = seq.id.clone().unwrap_or_default();
+    if seq.revision > 0 || !seq.time.is_empty() {
+        identification.push_str(&format!(" #{} {}", seq.revision, seq.time));
+    }
+    line('I', identification.trim());
+
+    let terms: Vec<String> = seq.data.iter().map(|t| t.to_string()).collect();
+    let mut tags = ['S', 'T'].into_iter();
+    let mut chunk = String::new();
+    for (i, term) in terms.iter().enumerate() {
+        if !chunk.is_empty() && chunk.len() + term.len() + 1 > DATA_LINE_LEN {
+            line(tags.next().unwrap_or('U'), &chunk);
+            chunk.clear();
+        }
+        chunk.push_str(term);
+        if i + 1 < terms.len() {
+            chunk.push(',');
+        }
+    }
+    line(tags.next().unwrap_or('U'), &chunk);
+
+    line('N', &seq.name);
+    let fields = [
+        ('C', &seq.comment),
+        ('D', &seq.reference),
+        ('H', &seq.link),
+        ('F', &seq.formula),
+        ('e', &seq.example),
+        ('p', &seq.maple),
+        ('t', &seq.mathematica),
+        ('o', &seq.program),
+        ('Y', &seq.xref),
+    ];
+    for (tag, lines) in fields {
+        for content in lines {
+            line(tag, content);
+        }
+    }
+    let keywords: Vec<&str> = seq.keyword.iter().map(|kw| kw.as_str()).collect();
+    line('K', &keywords.join(","));
+    line('O', &seq.offset.to_string());
+    line('A', &seq.author);
+    for content in &seq.ext {
+        line('E', content);
+    }
+    out
+}
+
+/// Parse the content of the `%I` line, e.g. `M0692 N0256 #1047 Oct 10 2025
+/// 10:12:45`: handbook IDs, then the revision number and modification time.
+fn parse_identification(entry: &mut OeisEntry, content: &str) {
+    let (ids, rest) = match content.find('#') {
+        Some(i) => (&content[..i], &content[i + 1..]),
+        None => (content, ""),
+    };
+    let ids = ids.trim();
+    if !ids.is_empty() {
+        entry.id = Some(ids.to_owned());
+    }
+    let (revision, time) = rest.split_once(' ').unwrap_or((rest, ""));
+    entry.revision = revision.parse().unwrap_or_default();
+    entry.time = time.trim().to_owned();
+}
+
+fn append(field: &mut String, content: &str) {
+    if !field.is_empty() {
+        field.push(' ');
+    }
+    field.push_str(content);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A `fmt=text` search response with two entries, abridged.
+    const RESPONSE: &str = "\
+# Greetings from The On-Line Encyclopedia of Integer Sequences! http://oeis.org/
+
+Search: seq:1,3,4,7,11
+Showing 1-2 of 2
+
+%I A000032 M2341 N0924 #485 Oct 05 2025 17:59:40
+%S A000032 2,1,3,4,7,11,18,29,47,76,123,199,322,521,843,1364,2207,3571,5778,9349,
+%T A000032 15127,24476,39603,64079,103682,167761,271443,439204,710647,1149851,
+%U A000032 1860498,3010349,4870847,7881196,12752043,20633239,33385282
+%N A000032 Lucas numbers beginning at 2: L(n) = L(n-1) + L(n-2), L(0) = 2, L(1) = 1.
+%C A000032 Also called Lucas numbers.
+%C A000032 L(n) is the number of matchings in the cycle graph C_n, for n >= 3.
+%D A000032 E. Lucas, Théorie des Nombres, 1891.
+%H A000032 N. J. A. Sloane, <a href=\"/A000032/b000032.txt\">Table of n, L(n) for n = 0..500</a>
+%F A000032 L(n) = F(n-1) + F(n+1).
+%e A000032 L(3) = L(2) + L(1) = 3 + 1 = 4.
+%p A000032 A000032 := proc(n) option remember; if n <= 1 then 2-n; else procname(n-1)+procname(n-2); fi; end:
+%t A000032 LucasL[Range[0, 40]]
+%o A000032 (PARI) a(n)=fibonacci(n-1)+fibonacci(n+1)
+%o A000032 (Haskell)
+%o A000032 a000032 n = a000032_list !! n
+%Y A000032 Cf. A000045, A000204.
+%K A000032 nonn,nice,easy,core
+%O A000032 0,1
+%A A000032 _N. J. A. Sloane_
+%E A000032 Edited by _N. J. A. Sloane_, Oct 2025
+%Z A000032 An unknown field, which is skipped.
+
+%I A000204 M2341 #218 Sep 01 2025 10:00:00
+%S A000204 1,3,4,7,11,18,29,47,76,123,199,322,521,843,1364,2207,3571,5778,9349,
+%T A000204 15127,24476,39603,64079,103682,167761,271443,439204,710647
+%N A000204 Lucas numbers beginning with 1: L(n) = L(n-1) + L(n-2)
+%N A000204 with L(1) = 1, L(2) = 3.
+%K A000204 nonn,easy,core,nice
+%O A000204 1,2
+%A A000204 _N. J. A. Sloane_
+
+# Content is available under The OEIS End-User License Agreement: http://oeis.org/LICENSE
+";
+
+    fn sequences(text: &str) -> Vec<OeisSequence> {
+        parse_internal(text)
+            .unwrap()
+            .into_iter()
+            .map(|entry| OeisSequence::try_from(entry).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn parse_response() {
+        let seqs = sequences(RESPONSE);
+        assert_eq!(seqs.len(), 2);
+
+        let lucas = &seqs[0];
+        assert_eq!(lucas.number, 32);
+        assert_eq!(lucas.id.as_deref(), Some("M2341 N0924"));
+        assert_eq!(lucas.revision, 485);
+        assert_eq!(lucas.time, "Oct 05 2025 17:59:40");
+        assert_eq!(lucas.data.len(), 37);
+        assert_eq!(lucas.data[36], 33385282.into());
+        assert_eq!(lucas.comment.len(), 2);
+        assert_eq!(lucas.program.len(), 3);
+        assert_eq!(lucas.xref, ["Cf. A000045, A000204."]);
+        assert_eq!(lucas.offset.to_string(), "0,1");
+        assert_eq!(lucas.author, "_N. J. A. Sloane_");
+        assert_eq!(lucas.ext.len(), 1);
+
+        let lucas1 = &seqs[1];
+        assert_eq!(lucas1.number, 204);
+        assert_eq!(
+            lucas1.name,
+            "Lucas numbers beginning with 1: L(n) = L(n-1) + L(n-2) with L(1) = 1, L(2) = 3."
+        );
+        assert!(lucas1.comment.is_empty());
+    }
+
+    #[test]
+    fn invalid_line() {
+        let err = parse_internal("%I A000045\n%S X000045 0,1,1\n").unwrap_err();
+        assert_eq!(err.line, 2);
+        assert_eq!(err.content, "%S X000045 0,1,1");
+    }
+
+    #[test]
+    fn round_trip() {
+        for seq in sequences(RESPONSE) {
+            let written = write_internal(&seq);
+            assert_eq!(sequences(&written), [seq], "{written}");
+        }
+    }
+
+    #[test]
+    fn write_wraps_terms() {
+        let seq = &sequences(RESPONSE)[0];
+        let written = write_internal(seq);
+        let data_lines: Vec<_> = written
+            .lines()
+            .filter(|line| ["%S", "%T", "%U"].iter().any(|tag| line.starts_with(tag)))
+            .collect();
+        assert!(data_lines.len() >= 3);
+        assert!(data_lines[0].starts_with("%S A000032 2,1,3,4,"));
+        assert!(data_lines[1].starts_with("%T A000032 "));
+        for line in data_lines {
+            assert!(line.len() <= "%S A000032 ".len() + DATA_LINE_LEN, "{line}");
+        }
+    }
+}
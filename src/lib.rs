@@ -0,0 +1,31 @@
+#[cfg(feature = "async")]
+pub mod asynchronous;
+pub mod bfile;
+pub mod cache;
+pub mod chaos;
+pub mod classify;
+pub mod client;
+pub mod clock;
+pub mod error;
+pub mod fetch;
+pub mod http;
+pub mod internal;
+pub mod limit;
+pub mod link;
+pub mod lock;
+pub mod mastodon;
+pub mod mirror;
+pub mod oeis;
+pub mod paths;
+pub mod program;
+pub mod query;
+pub mod retry;
+pub mod sanitize;
+#[cfg(feature = "sqlite")]
+pub mod store;
+pub mod terms;
+pub mod text;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod webhook;
+pub mod xref;
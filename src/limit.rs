@@ -0,0 +1,113 @@
+use crate::http::{HttpTransport, Request, Response, TransportError};
+use std::collections::HashMap;
+use std::io::{self, Read};
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Condvar, Mutex};
+
+/// A transport limiting the number of concurrent requests to some hosts,
+/// so that clients shared between threads do not overload them.
+///
+/// A request holds its slot until its response body is dropped. Requests
+/// to a host without a limit are passed through directly.
+#[derive(Debug, Clone)]
+pub struct HostLimits {
+    inner: Arc<dyn HttpTransport>,
+    limits: HashMap<String, usize>,
+    in_flight: Arc<InFlight>,
+}
+
+#[derive(Debug, Default)]
+struct InFlight {
+    counts: Mutex<HashMap<String, usize>>,
+    released: Condvar,
+}
+
+impl HostLimits {
+    /// Wrap `inner`, initially without any limit.
+    pub fn new(inner: Arc<dyn HttpTransport>) -> Self {
+        Self {
+            inner,
+            limits: HashMap::new(),
+            in_flight: Arc::default(),
+        }
+    }
+
+    /// Allow at most `max` concurrent requests to `host` (e.g. `oeis.org`).
+    pub fn with_limit(mut self, host: &str, max: NonZeroUsize) -> Self {
+        self.limits.insert(host.to_ascii_lowercase(), max.get());
+        self
+    }
+
+    /// Block until a slot is available for `host`, and take it.
+    fn acquire(&self, host: String, max: usize) -> Permit {
+        let mut counts = self.in_flight.counts.lock().unwrap();
+        while counts.get(&host).is_some_and(|n| *n >= max) {
+            counts = self.in_flight.released.wait(counts).unwrap();
+        }
+        *counts.entry(host.clone()).or_default() += 1;
+        Permit {
+            in_flight: Arc::clone(&self.in_flight),
+            host,
+        }
+    }
+}
+
+impl HttpTransport for HostLimits {
+    fn send(&self, request: Request) -> Result<Response, TransportError> {
+        let host = host(&request.url).to_ascii_lowercase();
+        let Some(&max) = self.limits.get(&host) else {
+            return self.inner.send(request);
+        };
+        let permit = self.acquire(host, max);
+        let response = self.inner.send(request)?;
+        Ok(Response {
+            body: Box::new(LimitedBody {
+                body: response.body,
+                _permit: permit,
+            }),
+            ..response
+        })
+    }
+}
+
+/// A slot taken for a request, released when dropped.
+struct Permit {
+    in_flight: Arc<InFlight>,
+    host: String,
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        let mut counts = self.in_flight.counts.lock().unwrap();
+        if let Some(n) = counts.get_mut(&self.host) {
+            *n -= 1;
+        }
+        self.in_flight.released.notify_all();
+    }
+}
+
+struct LimitedBody {
+    body: Box<dyn Read + Send>,
+    _permit: Permit,
+}
+
+impl Read for LimitedBody {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.body.read(buf)
+    }
+}
+
+/// Host part of `url`, without user information nor port, as expected by
+/// [`HostLimits::with_limit`].
+pub fn host(url: &str) -> &str {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or("");
+    let host = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    if host.starts_with('[') {
+        // IPv6 literal, e.g. `[::1]:8080`
+        return host.split_inclusive(']').next().unwrap_or(host);
+    }
+    host.split(':').next().unwrap_or(host)
+}
@@ -0,0 +1,151 @@
+use crate::oeis::OeisSequence;
+
+/// A link from the links section of an OEIS entry, e.g.
+/// `N. J. A. Sloane, <a href="/A000045/b000045.txt">Table of n, a(n) for n =
+/// 0..500</a>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    /// The author of the linked resource, if given before the anchor.
+    pub author: Option<String>,
+    /// The text of the anchor, without HTML tags.
+    pub text: String,
+    /// The absolute URL of the anchor.
+    pub url: String,
+}
+
+impl Link {
+    /// Parse a line of the links section, using its first anchor. Returns
+    /// `None` if the line has no anchor.
+    pub fn parse(line: &str) -> Option<Self> {
+        let start = line.find("<a ")?;
+        let tag = &line[start..];
+        let href = tag.find("href=\"")? + "href=\"".len();
+        let href_end = href + tag[href..].find('"')?;
+        let text = href_end + tag[href_end..].find('>')? + 1;
+        let text_end = text + tag[text..].find("</a>")?;
+        let author = line[..start].trim().trim_end_matches(',').trim();
+        Some(Self {
+            author: (!author.is_empty()).then(|| decode_entities(author)),
+            text: decode_entities(&strip_tags(&tag[text..text_end])),
+            url: absolute_url(&decode_entities(&tag[href..href_end])),
+        })
+    }
+
+    /// Whether the link points to the b-file of a sequence.
+    pub fn is_bfile(&self) -> bool {
+        self.url.starts_with("https://oeis.org/A")
+            && self
+                .url
+                .rsplit('/')
+                .next()
+                .is_some_and(|file| file.starts_with('b') && file.ends_with(".txt"))
+    }
+
+    /// Whether the link points to arXiv.
+    pub fn is_arxiv(&self) -> bool {
+        self.url.contains("arxiv.org/")
+    }
+}
+
+/// The links of `seq` that contain an anchor, in order.
+pub fn links(seq: &OeisSequence) -> Vec<Link> {
+    seq.link
+        .iter()
+        .filter_map(|line| Link::parse(line))
+        .collect()
+}
+
+/// Make URLs relative to oeis.org absolute.
+fn absolute_url(url: &str) -> String {
+    if url.starts_with('/') {
+        format!("https://oeis.org{url}")
+    } else {
+        url.to_owned()
+    }
+}
+
+fn strip_tags(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut in_tag = false;
+    for c in s.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            c if !in_tag => out.push(c),
+            _ => {}
+        }
+    }
+    out
+}
+
+/// Decode the HTML entities used in OEIS entries.
+fn decode_entities(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link(author: Option<&str>, text: &str, url: &str) -> Option<Link> {
+        Some(Link {
+            author: author.map(str::to_owned),
+            text: text.to_owned(),
+            url: url.to_owned(),
+        })
+    }
+
+    #[test]
+    fn parse() {
+        let cases = [
+            (
+                r#"N. J. A. Sloane, <a href="/A000045/b000045.txt">Table of n, a(n) for n = 0..500</a>"#,
+                link(
+                    Some("N. J. A. Sloane"),
+                    "Table of n, a(n) for n = 0..500",
+                    "https://oeis.org/A000045/b000045.txt",
+                ),
+            ),
+            (
+                r#"<a href="https://arxiv.org/abs/1104.1234" title="On &quot;tilings&quot;" target="_blank">Some &amp; <i>other</i> tilings</a>, 2011."#,
+                link(
+                    None,
+                    "Some & other tilings",
+                    "https://arxiv.org/abs/1104.1234",
+                ),
+            ),
+            (
+                r#"P. Erd&#39;os, <a rel="nofollow" href="http://example.com/?a=1&amp;b=2">Notes</a>"#,
+                link(Some("P. Erd'os"), "Notes", "http://example.com/?a=1&b=2"),
+            ),
+            (
+                r#"<a href="/index/Rec#order_02">Index entries for linear recurrences</a>"#,
+                link(
+                    None,
+                    "Index entries for linear recurrences",
+                    "https://oeis.org/index/Rec#order_02",
+                ),
+            ),
+            ("R. K. Guy, Unsolved Problems in Number Theory, 2004.", None),
+            (r#"<a name="top">Top</a>"#, None),
+            (r#"<a href="/A000045">Unclosed"#, None),
+        ];
+        for (line, expected) in cases {
+            assert_eq!(Link::parse(line), expected, "{line:?}");
+        }
+    }
+
+    #[test]
+    fn kinds() {
+        let bfile = Link::parse(r#"<a href="/A000045/b000045.txt">Table</a>"#).unwrap();
+        assert!(bfile.is_bfile() && !bfile.is_arxiv());
+        let arxiv = Link::parse(r#"<a href="https://arxiv.org/abs/1104.1234">Paper</a>"#).unwrap();
+        assert!(arxiv.is_arxiv() && !arxiv.is_bfile());
+        let other = Link::parse(r#"<a href="/A000045/a000045.pdf">Notes</a>"#).unwrap();
+        assert!(!other.is_bfile() && !other.is_arxiv());
+    }
+}
@@ -0,0 +1,86 @@
+use std::fmt;
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::{self, Read, Seek, Write};
+use std::path::Path;
+use std::process;
+
+/// An exclusive lock on a directory, so that a single instance of the bot
+/// uses it at a time.
+///
+/// The lock is held on a file in the directory, which contains the process
+/// ID of its holder. It is released when the `InstanceLock` is dropped or
+/// the process exits, even if it crashes; the file itself is left in place.
+#[derive(Debug)]
+pub struct InstanceLock {
+    _file: File,
+}
+
+/// Name of the lock file.
+pub const LOCK_FILE: &str = "oeis_bot.lock";
+
+#[derive(Debug)]
+pub enum LockError {
+    /// Another process holds the lock.
+    Locked {
+        /// The process ID of the holder, if it could be read.
+        pid: Option<u32>,
+    },
+    Io(io::Error),
+}
+
+impl fmt::Display for LockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LockError::Locked { pid: Some(pid) } => {
+                write!(f, "another instance is running (pid {pid})")
+            }
+            LockError::Locked { pid: None } => write!(f, "another instance is running"),
+            LockError::Io(e) => write!(f, "failed to lock instance: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for LockError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LockError::Io(e) => Some(e),
+            LockError::Locked { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for LockError {
+    fn from(e: io::Error) -> Self {
+        LockError::Io(e)
+    }
+}
+
+impl InstanceLock {
+    /// Lock `dir`, which is created if needed. Fails immediately with
+    /// [`LockError::Locked`] if another process holds the lock.
+    pub fn acquire(dir: impl AsRef<Path>) -> Result<Self, LockError> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(dir.join(LOCK_FILE))?;
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => {
+                let mut pid = String::new();
+                file.read_to_string(&mut pid)?;
+                return Err(LockError::Locked {
+                    pid: pid.trim().parse().ok(),
+                });
+            }
+            Err(TryLockError::Error(e)) => return Err(e.into()),
+        }
+        file.set_len(0)?;
+        file.rewind()?;
+        write!(file, "{}", process::id())?;
+        Ok(Self { _file: file })
+    }
+}
@@ -1,19 +1,270 @@
-mod error;
-mod fetch;
-mod mastodon;
-mod oeis;
-
+use oeis_bot::cache::HttpCache;
+use oeis_bot::chaos::Chaos;
+use oeis_bot::fetch::{Client, OEIS_MAX_CONCURRENT};
+use oeis_bot::http::{self, HttpConfig, HttpTransport};
+use oeis_bot::limit::{self, HostLimits};
+use oeis_bot::lock::InstanceLock;
+use oeis_bot::mastodon::{self, MASTODON_MAX_CONCURRENT, Mastodon, NumberStyle, Status};
+use oeis_bot::oeis::OeisSequence;
+use oeis_bot::paths::Paths;
+#[cfg(feature = "sqlite")]
+use oeis_bot::store::Store;
+use oeis_bot::webhook::{PostEvent, Webhooks};
 use std::env;
+#[cfg(feature = "sqlite")]
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How long cached OEIS responses are used without revalidation.
+const HTTP_CACHE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Content warning of the replies holding the details of sequences.
+const DETAILS_SPOILER: &str = "Tap for the math details";
+
+/// Number of statuses kept in the fallback pool, posted when the OEIS is
+/// unreachable.
+#[cfg(feature = "sqlite")]
+const POOL_SIZE: usize = 5;
 
 fn main() {
-    let seq = fetch::fetch_random();
-    let status = mastodon::format_status(&seq);
+    let paths = match data_dir_arg() {
+        Some(dir) => Some(Paths::in_dir(dir)),
+        None => Paths::platform(),
+    };
+    // Refuse to run alongside another instance, which could post twice.
+    let _lock = paths
+        .as_ref()
+        .map(|paths| InstanceLock::acquire(&paths.state_dir).unwrap_or_else(|e| panic!("{e}")));
 
+    let mut http_config = HttpConfig::default();
+    if let Ok(url) = env::var("OEIS_BOT_PROXY") {
+        http_config.proxy = Some(url);
+    }
     let instance_url = env::var("MASTODON_INSTANCE_URL")
         .expect("MASTODON_INSTANCE_URL environment variable must be set");
+    let transport = with_chaos(http::transport(&http_config).expect("invalid proxy URL"));
+    // Clients share the transport, so the limits apply to all of them.
+    let transport: Arc<dyn HttpTransport> = Arc::new(
+        HostLimits::new(transport)
+            .with_limit("oeis.org", OEIS_MAX_CONCURRENT)
+            .with_limit(limit::host(&instance_url), MASTODON_MAX_CONCURRENT),
+    );
+
+    let mut client = Client::new().with_transport(transport.clone());
+    if let Ok(contact) = env::var("OEIS_BOT_CONTACT") {
+        client = client.with_contact(&contact);
+    }
+    let cache_dir = env::var_os("OEIS_BOT_CACHE_DIR")
+        .map(PathBuf::from)
+        .or_else(|| Some(paths.as_ref()?.cache_dir.clone()));
+    if let Some(dir) = cache_dir {
+        client = client.with_http_cache(HttpCache::new(dir, HTTP_CACHE_TTL));
+    }
+
     let token = env::var("MASTODON_ACCESS_TOKEN")
         .expect("MASTODON_ACCESS_TOKEN environment variable must be set");
+    let mut webhooks = Webhooks::new(
+        env::var("OEIS_BOT_WEBHOOK_URLS")
+            .map(|urls| {
+                urls.split(',')
+                    .map(str::trim)
+                    .filter(|url| !url.is_empty())
+                    .map(str::to_owned)
+                    .collect()
+            })
+            .unwrap_or_default(),
+    )
+    .with_transport(transport.clone());
+    if let Ok(secret) = env::var("OEIS_BOT_WEBHOOK_SECRET") {
+        webhooks = webhooks.with_secret(&secret);
+    }
+    let publisher = Publisher {
+        mastodon: Mastodon::new(&instance_url, &token).with_transport(transport),
+        webhooks,
+        details: env::var_os("OEIS_BOT_SPOILER_DETAILS").is_some(),
+    };
+
+    #[cfg(feature = "sqlite")]
+    if let Some(path) = env::var_os("OEIS_BOT_DATABASE") {
+        // Relative paths are resolved in the data directory.
+        let path = match &paths {
+            Some(paths) => paths.data_dir.join(path),
+            None => PathBuf::from(path),
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).expect("failed to create data directory");
+        }
+        let mut store = Store::open(path).expect("failed to open database");
+        post_with_store(&client, &publisher, &mut store);
+        return;
+    }
+
+    let seq = client
+        .fetch_random()
+        .expect("failed to pick a random sequence");
+    let status = format_status(&client, &seq, None);
+    let posted = publisher.post(seq.number, &status);
+    publisher.post_details(&posted, &seq);
+}
+
+/// The directory given with `--data-dir`, if any. This is the only
+/// command-line option; everything else is configured with environment
+/// variables.
+fn data_dir_arg() -> Option<PathBuf> {
+    let mut args = env::args_os().skip(1);
+    let mut dir = None;
+    while let Some(arg) = args.next() {
+        if arg == "--data-dir" {
+            dir = Some(args.next().expect("--data-dir requires a path").into());
+        } else if let Some(value) = arg.to_str().and_then(|a| a.strip_prefix("--data-dir=")) {
+            dir = Some(value.into());
+        } else {
+            panic!("unknown argument {arg:?}");
+        }
+    }
+    dir
+}
+
+/// Where statuses are posted, and who is told about it.
+struct Publisher {
+    mastodon: Mastodon,
+    webhooks: Webhooks,
+    /// Whether to reply to posts with details behind a content warning.
+    details: bool,
+}
+
+impl Publisher {
+    /// Post `status` about sequence `number`, and notify the webhooks of the
+    /// outcome.
+    fn post(&self, number: u64, status: &str) -> Status {
+        let result = self.mastodon.post_status(status);
+        let event = PostEvent {
+            sequence: number,
+            status_url: result.as_ref().ok().and_then(|status| status.url.clone()),
+            backend: "mastodon".to_owned(),
+            error: result.as_ref().err().map(|e| e.to_string()),
+        };
+        for (url, e) in self.webhooks.notify(&event) {
+            eprintln!("failed to notify webhook {url}: {e}");
+        }
+        result.expect("failed to post status to Mastodon")
+    }
+
+    /// Reply to `status` with the details of `seq` behind a content warning,
+    /// if enabled. Failures are only reported, as the main post is out.
+    fn post_details(&self, status: &Status, seq: &OeisSequence) {
+        if !self.details {
+            return;
+        }
+        let Some(details) = mastodon::format_details(seq) else {
+            return;
+        };
+        if let Err(e) = self
+            .mastodon
+            .reply_with_spoiler(&status.id, DETAILS_SPOILER, &details)
+        {
+            eprintln!("failed to post details: {e}");
+        }
+    }
+}
+
+/// Format `seq` as a status, mentioning its number of known terms from the
+/// b-file.
+fn format_status(client: &Client, seq: &OeisSequence, commentary: Option<&str>) -> String {
+    let known_terms = client.fetch_term_count(seq.number).ok();
+    mastodon::format_status(seq, known_terms, commentary, number_style())
+}
+
+/// How to write the first terms of sequences, from the
+/// `OEIS_BOT_NUMBER_STYLE` environment variable.
+fn number_style() -> NumberStyle {
+    env::var("OEIS_BOT_NUMBER_STYLE")
+        .map(|style| style.parse().unwrap_or_else(|e| panic!("{e}")))
+        .unwrap_or_default()
+}
+
+/// Post a random sequence with its commentary and record it in `store`. If
+/// no sequence can be fetched from the OEIS, post one from the fallback
+/// pool instead, and refill the pool on the next successful run.
+#[cfg(feature = "sqlite")]
+fn post_with_store(client: &Client, publisher: &Publisher, store: &mut Store) {
+    let seq = match client.fetch_random() {
+        Ok(seq) => seq,
+        Err(e) => {
+            let (number, status) = store
+                .take_from_pool()
+                .expect("failed to read fallback pool from database")
+                .unwrap_or_else(|| panic!("failed to pick a random sequence: {e}"));
+            publisher.post(number, &status);
+            store
+                .record_post(number)
+                .expect("failed to record post in database");
+            return;
+        }
+    };
+    let commentary = store
+        .commentary(seq.number)
+        .expect("failed to read commentary from database");
+    let status = format_status(client, &seq, commentary.as_deref());
+    let posted = publisher.post(seq.number, &status);
+    publisher.post_details(&posted, &seq);
+    store
+        .save_sequence(&seq)
+        .and_then(|()| store.record_post(seq.number))
+        .expect("failed to record post in database");
+
+    while store
+        .pool_len()
+        .expect("failed to read fallback pool from database")
+        < POOL_SIZE
+    {
+        let Ok(seq) = client.fetch_random() else {
+            break;
+        };
+        let commentary = store
+            .commentary(seq.number)
+            .expect("failed to read commentary from database");
+        let status = format_status(client, &seq, commentary.as_deref());
+        store
+            .add_to_pool(seq.number, &status)
+            .expect("failed to add to fallback pool in database");
+    }
+}
 
-    mastodon::post_status(&instance_url, &token, &status)
-        .expect("failed to post status to Mastodon");
+/// Inject failures into HTTP requests if any of the `OEIS_BOT_CHAOS_*`
+/// environment variables is set. For testing only.
+fn with_chaos(transport: Arc<dyn HttpTransport>) -> Arc<dyn HttpTransport> {
+    let var = |name: &str, valid: fn(f64) -> bool, expected: &str| {
+        env::var(name).ok().map(|v| {
+            v.parse()
+                .ok()
+                .filter(|&v| valid(v))
+                .unwrap_or_else(|| panic!("{name} must be {expected}"))
+        })
+    };
+    let rate = |name| {
+        var(
+            name,
+            |v| (0.0..=1.0).contains(&v),
+            "a number between 0 and 1",
+        )
+    };
+    let error_rate = rate("OEIS_BOT_CHAOS_ERROR_RATE");
+    let delay_ms = var(
+        "OEIS_BOT_CHAOS_DELAY_MS",
+        |v| v >= 0.0 && Duration::try_from_secs_f64(v / 1000.0).is_ok(),
+        "a non-negative number",
+    );
+    let truncate_rate = rate("OEIS_BOT_CHAOS_TRUNCATE_RATE");
+    if error_rate.is_none() && delay_ms.is_none() && truncate_rate.is_none() {
+        return transport;
+    }
+    Arc::new(
+        Chaos::new(transport)
+            .with_error_rate(error_rate.unwrap_or(0.0))
+            .with_delay(Duration::from_secs_f64(delay_ms.unwrap_or(0.0) / 1000.0))
+            .with_truncate_rate(truncate_rate.unwrap_or(0.0)),
+    )
 }
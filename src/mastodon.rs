@@ -1,26 +1,408 @@
-use crate::oeis::OeisSequence;
-use ureq::Error;
+use crate::http::{self, HttpConfig, HttpTransport, Request, Response, TransportError};
+use crate::limit::{self, HostLimits};
+use crate::oeis::{ANumber, OeisSequence};
+use crate::sanitize::sanitize;
+use crate::text;
+use num_bigint::BigInt;
+use serde::Deserialize;
+use std::fmt;
+use std::num::NonZeroUsize;
+use std::str::FromStr;
+use std::sync::Arc;
 
-/// Format a sequence as a status message.
-pub fn format_status(seq: &OeisSequence) -> String {
-    let data: Vec<String> = seq.data.iter().map(|n| n.to_string()).collect();
+/// Format a sequence as a status message. Text taken from the OEIS entry
+/// is sanitized before inclusion, and long names are shortened.
+///
+/// `known_terms` is the total number of known terms (e.g. from the b-file),
+/// mentioned when there are more than the terms listed. `commentary` is
+/// text written by the operator about this sequence, added as is after the
+/// terms. The first terms are rendered in `style`, except for decimal
+/// expansions, which are written as the constant (see
+/// [`OeisSequence::decimal_expansion`]).
+pub fn format_status(
+    seq: &OeisSequence,
+    known_terms: Option<usize>,
+    commentary: Option<&str>,
+    style: NumberStyle,
+) -> String {
+    let known_terms = known_terms.filter(|n| *n > seq.data.len());
+    let mut body = match seq.decimal_expansion() {
+        Some(mut expansion) => {
+            if let Some(n) = known_terms {
+                expansion.push_str(&format!(" ({n} known digits)"));
+            }
+            expansion
+        }
+        None => {
+            let mut data: Vec<String> = seq
+                .data
+                .iter()
+                .enumerate()
+                .map(|(i, n)| {
+                    if i < STYLED_TERMS {
+                        style.render(n)
+                    } else {
+                        n.to_string()
+                    }
+                })
+                .collect();
+            if let Some(n) = known_terms {
+                data.push(format!("… ({n} known terms)"));
+            }
+            data.join(", ")
+        }
+    };
+    if let Some(commentary) = commentary {
+        body.push_str("\n\n");
+        body.push_str(commentary.trim());
+    }
     format!(
-        "OEIS sequence A{:06}\n{}\n\n{}\n\nhttps://oeis.org/A{}",
-        seq.number,
-        seq.name,
-        data.join(", "),
-        seq.number,
+        "OEIS sequence {}\n{}\n\n{}\n\n{}",
+        ANumber(seq.number),
+        sanitize(&text::shorten_name(&seq.name, MAX_NAME_WIDTH)),
+        body,
+        ANumber(seq.number).url(),
     )
 }
 
-/// Post a status to a Mastodon instance.
-///
-/// `instance_url` is the base URL (e.g. `https://mastodon.social`).
-/// `token` is a Bearer access token with `write:statuses` scope.
-pub fn post_status(instance_url: &str, token: &str, status: &str) -> Result<(), Error> {
-    let url = format!("{}/api/v1/statuses", instance_url.trim_end_matches('/'));
-    ureq::post(&url)
-        .header("Authorization", &format!("Bearer {token}"))
-        .send_form([("status", status)])?;
-    Ok(())
+/// Number of terms rendered in the [`NumberStyle`] of a status.
+const STYLED_TERMS: usize = 5;
+
+/// How the first terms of a sequence are written in a status.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NumberStyle {
+    /// Decimal digits, e.g. `12`.
+    #[default]
+    Plain,
+    /// Keycap emoji digits, e.g. `1️⃣2️⃣`.
+    Emoji,
+    /// Tally marks, e.g. `𝍸𝍷𝍷` for 7. Numbers outside 1 to 20 are written
+    /// in decimal digits.
+    Tally,
+}
+
+impl NumberStyle {
+    /// Write `n` in this style.
+    pub fn render(self, n: &BigInt) -> String {
+        match self {
+            NumberStyle::Plain => n.to_string(),
+            NumberStyle::Emoji => n
+                .to_string()
+                .chars()
+                .map(|c| match c {
+                    '-' => "➖".to_owned(),
+                    d => format!("{d}\u{FE0F}\u{20E3}"),
+                })
+                .collect(),
+            NumberStyle::Tally => match u32::try_from(n) {
+                Ok(n @ 1..=20) => "𝍸".repeat(n as usize / 5) + &"𝍷".repeat(n as usize % 5),
+                _ => n.to_string(),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ParseNumberStyleError(pub String);
+
+impl fmt::Display for ParseNumberStyleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown number style: {:?}", self.0)
+    }
+}
+
+impl std::error::Error for ParseNumberStyleError {}
+
+impl FromStr for NumberStyle {
+    type Err = ParseNumberStyleError;
+
+    /// Parse `plain`, `emoji` or `tally`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "plain" => Ok(NumberStyle::Plain),
+            "emoji" => Ok(NumberStyle::Emoji),
+            "tally" => Ok(NumberStyle::Tally),
+            other => Err(ParseNumberStyleError(other.to_owned())),
+        }
+    }
+}
+
+/// Maximum width of the details posted by [`format_details`], below the
+/// 500 characters allowed by default by Mastodon.
+const MAX_DETAILS_WIDTH: usize = 480;
+
+/// Maximum width of the sequence name in a status, see
+/// [`text::shorten_name`].
+const MAX_NAME_WIDTH: usize = 200;
+
+/// Format the first comment of a sequence, or its first formula if it has
+/// no comments, to be posted behind a content warning (see
+/// [`Mastodon::reply_with_spoiler`]). Returns `None` if the sequence has
+/// neither.
+pub fn format_details(seq: &OeisSequence) -> Option<String> {
+    let details = [&seq.comment, &seq.formula].into_iter().find_map(|field| {
+        field
+            .iter()
+            .map(|line| line.trim())
+            .find(|line| !line.is_empty())
+    })?;
+    Some(text::truncate_with_ellipsis(&sanitize(details), MAX_DETAILS_WIDTH).into_owned())
+}
+
+/// Maximum number of concurrent requests to the Mastodon instance by
+/// default.
+pub const MASTODON_MAX_CONCURRENT: NonZeroUsize = NonZeroUsize::new(4).unwrap();
+
+/// A Mastodon account to post to.
+#[derive(Debug, Clone)]
+pub struct Mastodon {
+    instance_url: String,
+    token: String,
+    transport: Arc<dyn HttpTransport>,
+}
+
+#[derive(Debug)]
+pub enum PostError {
+    Transport(TransportError),
+    /// The instance answered with an unexpected HTTP status.
+    Status(u16),
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for PostError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PostError::Transport(e) => write!(f, "HTTP error: {e}"),
+            PostError::Status(status) => write!(f, "HTTP error: status {status}"),
+            PostError::Json(e) => write!(f, "invalid JSON response: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for PostError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PostError::Transport(e) => Some(e),
+            PostError::Json(e) => Some(e),
+            PostError::Status(_) => None,
+        }
+    }
+}
+
+impl From<TransportError> for PostError {
+    fn from(e: TransportError) -> Self {
+        PostError::Transport(e)
+    }
+}
+
+impl From<serde_json::Error> for PostError {
+    fn from(e: serde_json::Error) -> Self {
+        PostError::Json(e)
+    }
+}
+
+impl Mastodon {
+    /// `instance_url` is the base URL (e.g. `https://mastodon.social`).
+    /// `token` is a Bearer access token with `write:statuses` scope.
+    pub fn new(instance_url: &str, token: &str) -> Self {
+        let transport = HostLimits::new(http::default_transport())
+            .with_limit(limit::host(instance_url), MASTODON_MAX_CONCURRENT);
+        Self {
+            instance_url: instance_url.trim_end_matches('/').to_owned(),
+            token: token.to_owned(),
+            transport: Arc::new(transport),
+        }
+    }
+
+    /// Send requests with `transport`. It may be shared with other clients,
+    /// e.g. [`Client`](crate::fetch::Client). The number of concurrent
+    /// requests is not limited unless `transport` is a [`HostLimits`].
+    pub fn with_transport(mut self, transport: Arc<dyn HttpTransport>) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    /// Send requests with the default transport, configured with `config`
+    /// (timeouts and proxy) and limited to [`MASTODON_MAX_CONCURRENT`]
+    /// concurrent requests to the instance. Fails if the proxy URL is
+    /// invalid.
+    pub fn with_http_config(self, config: &HttpConfig) -> Result<Self, TransportError> {
+        let transport = HostLimits::new(http::transport(config)?)
+            .with_limit(limit::host(&self.instance_url), MASTODON_MAX_CONCURRENT);
+        Ok(self.with_transport(Arc::new(transport)))
+    }
+
+    /// Post a status.
+    pub fn post_status(&self, status: &str) -> Result<Status, PostError> {
+        let response = self.send(self.post("/api/v1/statuses").form("status", status))?;
+        Ok(serde_json::from_reader(response.body)?)
+    }
+
+    /// Post `status` in reply to the status `id`, with its text hidden
+    /// behind the content warning `spoiler_text`.
+    pub fn reply_with_spoiler(
+        &self,
+        id: &str,
+        spoiler_text: &str,
+        status: &str,
+    ) -> Result<Status, PostError> {
+        let req = self
+            .post("/api/v1/statuses")
+            .form("status", status)
+            .form("spoiler_text", spoiler_text)
+            .form("in_reply_to_id", id);
+        let response = self.send(req)?;
+        Ok(serde_json::from_reader(response.body)?)
+    }
+
+    /// Pin a status of the account to its profile. Requires the
+    /// `write:accounts` scope.
+    pub fn pin_status(&self, id: &str) -> Result<(), PostError> {
+        self.send(self.post(&format!("/api/v1/statuses/{id}/pin")))?;
+        Ok(())
+    }
+
+    /// Unpin a status of the account from its profile. Requires the
+    /// `write:accounts` scope.
+    pub fn unpin_status(&self, id: &str) -> Result<(), PostError> {
+        self.send(self.post(&format!("/api/v1/statuses/{id}/unpin")))?;
+        Ok(())
+    }
+
+    /// Replace the profile metadata fields of the account with `fields`, as
+    /// `(name, value)` pairs. Mastodon allows at most 4 fields. Requires the
+    /// `write:accounts` scope.
+    pub fn update_profile_fields(&self, fields: &[(&str, &str)]) -> Result<(), PostError> {
+        let mut req = self.patch("/api/v1/accounts/update_credentials");
+        for (i, (name, value)) in fields.iter().enumerate() {
+            req = req
+                .form(&format!("fields_attributes[{i}][name]"), name)
+                .form(&format!("fields_attributes[{i}][value]"), value);
+        }
+        self.send(req)?;
+        Ok(())
+    }
+
+    fn post(&self, path: &str) -> Request {
+        Request::post(&format!("{}{path}", self.instance_url))
+            .header("Authorization", &format!("Bearer {}", self.token))
+    }
+
+    fn patch(&self, path: &str) -> Request {
+        Request::patch(&format!("{}{path}", self.instance_url))
+            .header("Authorization", &format!("Bearer {}", self.token))
+    }
+
+    /// Send `req`, failing unless the response has a success status.
+    fn send(&self, req: Request) -> Result<Response, PostError> {
+        let response = self.transport.send(req)?;
+        if !response.is_success() {
+            return Err(PostError::Status(response.status));
+        }
+        Ok(response)
+    }
+}
+
+/// A status posted to Mastodon.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Status {
+    /// Identifier of the status on the instance.
+    pub id: String,
+    /// URL of the HTML representation of the status.
+    #[serde(default)]
+    pub url: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::internal::parse_internal;
+
+    fn sequence(text: &str) -> OeisSequence {
+        OeisSequence::try_from(parse_internal(text).unwrap().remove(0)).unwrap()
+    }
+
+    fn render(style: NumberStyle, n: i64) -> String {
+        style.render(&BigInt::from(n))
+    }
+
+    #[test]
+    fn render_plain() {
+        let rendered: Vec<_> = [-12, 0, 1, 20, 21, 123]
+            .into_iter()
+            .map(|n| render(NumberStyle::Plain, n))
+            .collect();
+        assert_eq!(rendered, ["-12", "0", "1", "20", "21", "123"]);
+    }
+
+    #[test]
+    fn render_emoji() {
+        assert_eq!(render(NumberStyle::Emoji, 0), "0\u{FE0F}\u{20E3}");
+        assert_eq!(render(NumberStyle::Emoji, 7), "7\u{FE0F}\u{20E3}");
+        assert_eq!(
+            render(NumberStyle::Emoji, 20),
+            "2\u{FE0F}\u{20E3}0\u{FE0F}\u{20E3}"
+        );
+        assert_eq!(
+            render(NumberStyle::Emoji, 123),
+            "1\u{FE0F}\u{20E3}2\u{FE0F}\u{20E3}3\u{FE0F}\u{20E3}"
+        );
+        assert_eq!(render(NumberStyle::Emoji, -4), "➖4\u{FE0F}\u{20E3}");
+    }
+
+    #[test]
+    fn render_tally() {
+        assert_eq!(render(NumberStyle::Tally, 1), "𝍷");
+        assert_eq!(render(NumberStyle::Tally, 4), "𝍷𝍷𝍷𝍷");
+        assert_eq!(render(NumberStyle::Tally, 5), "𝍸");
+        assert_eq!(render(NumberStyle::Tally, 7), "𝍸𝍷𝍷");
+        assert_eq!(render(NumberStyle::Tally, 20), "𝍸𝍸𝍸𝍸");
+        // Outside 1 to 20, numbers are written in decimal digits.
+        assert_eq!(render(NumberStyle::Tally, 0), "0");
+        assert_eq!(render(NumberStyle::Tally, 21), "21");
+        assert_eq!(render(NumberStyle::Tally, -3), "-3");
+    }
+
+    #[test]
+    fn format_status_styles() {
+        let seq = sequence(
+            "%S A000001 -1,0,1,5,21,-3,8\n%N A000001 A test sequence.\n%O A000001 0,3\n%K A000001 sign\n",
+        );
+        let status = |style| format_status(&seq, Some(100), None, style);
+        assert_eq!(
+            status(NumberStyle::Plain),
+            "OEIS sequence A000001\nA test sequence.\n\n\
+             -1, 0, 1, 5, 21, -3, 8, … (100 known terms)\n\n\
+             https://oeis.org/A000001"
+        );
+        assert_eq!(
+            status(NumberStyle::Emoji),
+            "OEIS sequence A000001\nA test sequence.\n\n\
+             ➖1\u{FE0F}\u{20E3}, 0\u{FE0F}\u{20E3}, 1\u{FE0F}\u{20E3}, 5\u{FE0F}\u{20E3}, \
+             2\u{FE0F}\u{20E3}1\u{FE0F}\u{20E3}, -3, 8, … (100 known terms)\n\n\
+             https://oeis.org/A000001"
+        );
+        assert_eq!(
+            status(NumberStyle::Tally),
+            "OEIS sequence A000001\nA test sequence.\n\n\
+             -1, 0, 𝍷, 𝍸, 21, -3, 8, … (100 known terms)\n\n\
+             https://oeis.org/A000001"
+        );
+    }
+
+    #[test]
+    fn format_status_decimal_expansion() {
+        let seq = sequence(
+            "%S A000796 3,1,4,1,5,9,2,6,5\n%N A000796 Decimal expansion of Pi.\n%O A000796 1,1\n%K A000796 cons,nonn\n",
+        );
+        // Decimal expansions are written as the constant, whatever the style.
+        for style in [NumberStyle::Plain, NumberStyle::Emoji, NumberStyle::Tally] {
+            assert_eq!(
+                format_status(&seq, Some(20000), Some("Happy Pi Day!"), style),
+                "OEIS sequence A000796\nDecimal expansion of Pi.\n\n\
+                 3.14159265... (20000 known digits)\n\nHappy Pi Day!\n\n\
+                 https://oeis.org/A000796"
+            );
+        }
+    }
 }
@@ -0,0 +1,249 @@
+use crate::error::FetchError;
+use crate::fetch::Client;
+use crate::oeis::OeisSequence;
+use flate2::read::GzDecoder;
+use num_bigint::BigInt;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{self, BufRead, BufReader};
+
+const STRIPPED_URL: &str = "https://oeis.org/stripped.gz";
+const NAMES_URL: &str = "https://oeis.org/names.gz";
+
+/// A sequence as stored in the local mirror.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MirrorEntry {
+    /// Human-readable name/description.
+    pub name: String,
+    /// The sequence values.
+    pub data: Vec<BigInt>,
+}
+
+impl MirrorEntry {
+    /// The fields of this entry that differ from the live sequence `seq`,
+    /// e.g. because the mirror is out of date.
+    pub fn diff(&self, seq: &OeisSequence) -> Vec<FieldDiff> {
+        let join = |data: &[BigInt]| {
+            data.iter()
+                .map(|n| n.to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        let mut diffs = Vec::new();
+        if self.name != seq.name {
+            diffs.push(FieldDiff {
+                field: "name",
+                mirror: self.name.clone(),
+                live: seq.name.clone(),
+            });
+        }
+        if self.data != seq.data {
+            diffs.push(FieldDiff {
+                field: "data",
+                mirror: join(&self.data),
+                live: join(&seq.data),
+            });
+        }
+        diffs
+    }
+}
+
+/// A field that differs between a mirror entry and the live sequence, see
+/// [`MirrorEntry::diff`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldDiff {
+    /// Name of the field (e.g. `"name"`).
+    pub field: &'static str,
+    /// Value in the mirror.
+    pub mirror: String,
+    /// Value on oeis.org.
+    pub live: String,
+}
+
+/// A local copy of the names and terms of all OEIS sequences, built from
+/// the bulk dumps `stripped.gz` and `names.gz`.
+///
+/// This allows lookups without any request to oeis.org once the dumps have
+/// been downloaded.
+#[derive(Debug, Clone, Default)]
+pub struct Mirror {
+    entries: BTreeMap<u64, MirrorEntry>,
+}
+
+/// Changes applied to a mirror by [`Mirror::sync`] or [`Mirror::update`].
+#[derive(Debug, Clone, Default)]
+pub struct SyncReport {
+    /// A-numbers of sequences that were not in the mirror.
+    pub added: Vec<u64>,
+    /// A-numbers of sequences whose name or terms changed.
+    pub changed: Vec<u64>,
+    /// A-numbers of sequences that are no longer in the dumps.
+    pub removed: Vec<u64>,
+}
+
+impl SyncReport {
+    /// Whether the mirror was already up to date.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.changed.is_empty() && self.removed.is_empty()
+    }
+}
+
+#[derive(Debug)]
+pub enum ParseDumpError {
+    Io(io::Error),
+    InvalidLine { line: usize, content: String },
+}
+
+impl fmt::Display for ParseDumpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseDumpError::Io(e) => write!(f, "I/O error while reading OEIS dump: {e}"),
+            ParseDumpError::InvalidLine { line, content } => {
+                write!(f, "invalid line {line} in OEIS dump: {content:?}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseDumpError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ParseDumpError::Io(e) => Some(e),
+            ParseDumpError::InvalidLine { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for ParseDumpError {
+    fn from(e: io::Error) -> Self {
+        ParseDumpError::Io(e)
+    }
+}
+
+impl Mirror {
+    /// Download and parse the bulk dumps from oeis.org. The compressed dumps
+    /// are parsed as they are streamed, without being stored in full. They
+    /// are downloaded one after the other.
+    pub fn download(client: &Client) -> Result<Self, FetchError> {
+        let mut mirror = Self::default();
+        let stripped = client.get_reader(STRIPPED_URL)?;
+        mirror.read_stripped(BufReader::new(GzDecoder::new(stripped)))?;
+        let names = client.get_reader(NAMES_URL)?;
+        mirror.read_names(BufReader::new(GzDecoder::new(names)))?;
+        Ok(mirror)
+    }
+
+    /// Build a mirror from the uncompressed contents of `stripped` (lines of
+    /// the form `A000045 ,0,1,1,2,3,`) and `names` (lines of the form
+    /// `A000045 Fibonacci numbers`). Lines starting with `#` are ignored.
+    pub fn from_dumps(stripped: impl BufRead, names: impl BufRead) -> Result<Self, ParseDumpError> {
+        let mut mirror = Self::default();
+        mirror.read_stripped(stripped)?;
+        mirror.read_names(names)?;
+        Ok(mirror)
+    }
+
+    fn read_stripped(&mut self, stripped: impl BufRead) -> Result<(), ParseDumpError> {
+        parse_dump(stripped, |number, data| {
+            let data = data
+                .split(',')
+                .filter(|s| !s.is_empty())
+                .map(|s| s.parse().ok())
+                .collect::<Option<Vec<BigInt>>>()?;
+            self.entries.entry(number).or_default().data = data;
+            Some(())
+        })
+    }
+
+    fn read_names(&mut self, names: impl BufRead) -> Result<(), ParseDumpError> {
+        parse_dump(names, |number, name| {
+            self.entries.entry(number).or_default().name = name.to_owned();
+            Some(())
+        })
+    }
+
+    /// Refresh the mirror by downloading the bulk dumps again, and report
+    /// which entries changed.
+    pub fn sync(&mut self, client: &Client) -> Result<SyncReport, FetchError> {
+        let latest = Self::download(client)?;
+        Ok(self.update(latest))
+    }
+
+    /// Replace the contents of the mirror with `latest`, and report which
+    /// entries changed.
+    pub fn update(&mut self, latest: Mirror) -> SyncReport {
+        let mut report = SyncReport::default();
+        for (id, entry) in &latest.entries {
+            match self.entries.get(id) {
+                None => report.added.push(*id),
+                Some(old) if old != entry => report.changed.push(*id),
+                Some(_) => {}
+            }
+        }
+        report.removed = self
+            .entries
+            .keys()
+            .filter(|id| !latest.entries.contains_key(id))
+            .copied()
+            .collect();
+        self.entries = latest.entries;
+        report
+    }
+
+    /// Look up a sequence by its A-number.
+    pub fn get(&self, id: u64) -> Option<&MirrorEntry> {
+        self.entries.get(&id)
+    }
+
+    /// Find the sequences containing `terms` in order and adjacent, without
+    /// any network request. Returns the matching A-numbers in increasing
+    /// order, or nothing if `terms` is empty.
+    pub fn identify_offline(&self, terms: &[BigInt]) -> Vec<u64> {
+        if terms.is_empty() {
+            return Vec::new();
+        }
+        self.iter()
+            .filter(|(_, entry)| entry.data.windows(terms.len()).any(|w| w == terms))
+            .map(|(id, _)| id)
+            .collect()
+    }
+
+    /// Iterate over all sequences, in increasing A-number order.
+    pub fn iter(&self) -> impl Iterator<Item = (u64, &MirrorEntry)> {
+        self.entries.iter().map(|(id, entry)| (*id, entry))
+    }
+
+    /// Number of sequences in the mirror.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Parse the lines of a dump, calling `f` with the A-number and the rest of
+/// each line. `f` returns `None` if the content is malformed.
+fn parse_dump(
+    reader: impl BufRead,
+    mut f: impl FnMut(u64, &str) -> Option<()>,
+) -> Result<(), ParseDumpError> {
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let parsed = line.split_once(' ').and_then(|(a, rest)| {
+            let number = a.strip_prefix('A')?.parse().ok()?;
+            f(number, rest.trim())
+        });
+        if parsed.is_none() {
+            return Err(ParseDumpError::InvalidLine {
+                line: i + 1,
+                content: line,
+            });
+        }
+    }
+    Ok(())
+}
@@ -1,11 +1,20 @@
+use crate::bfile::{BFile, TermMismatch};
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, FixedOffset};
 use num_bigint::BigInt;
-use serde::Deserialize;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
+use std::convert::Infallible;
 use std::fmt;
+use std::ops::{BitAnd, BitOr, Sub};
 use std::str::FromStr;
+use std::time::SystemTime;
 
 /// An OEIS keyword tag.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Keyword {
+    /// A-number reserved for a sequence not yet submitted (placeholder).
+    Allocated,
     /// Sequence is dependent on base used.
     Base,
     /// Sequence is too short to do any analysis with.
@@ -56,6 +65,8 @@ pub enum Keyword {
     Obsc,
     /// Included on probation; may be deleted later.
     Probation,
+    /// A-number of a deleted sequence, available for reuse (placeholder).
+    Recycled,
     /// Sequence contains negative numbers.
     Sign,
     /// An irregular (or funny-shaped) triangle of numbers read by rows.
@@ -70,11 +81,100 @@ pub enum Keyword {
     Walk,
     /// Depends on words for the sequence in some language.
     Word,
+    /// A keyword unknown to this crate, e.g. one recently added to the
+    /// OEIS.
+    Other(String),
 }
 
 impl Keyword {
-    pub fn as_str(self) -> &'static str {
+    /// All the keywords known to this crate, i.e. all but [`Keyword::Other`].
+    pub const ALL: &'static [Keyword] = &[
+        Keyword::Allocated,
+        Keyword::Base,
+        Keyword::Bref,
+        Keyword::Changed,
+        Keyword::Cofr,
+        Keyword::Cons,
+        Keyword::Core,
+        Keyword::Dead,
+        Keyword::Dumb,
+        Keyword::Dupe,
+        Keyword::Easy,
+        Keyword::Eigen,
+        Keyword::Fini,
+        Keyword::Frac,
+        Keyword::Full,
+        Keyword::Hard,
+        Keyword::Hear,
+        Keyword::Less,
+        Keyword::Look,
+        Keyword::More,
+        Keyword::Mult,
+        Keyword::New,
+        Keyword::Nice,
+        Keyword::Nonn,
+        Keyword::Obsc,
+        Keyword::Probation,
+        Keyword::Recycled,
+        Keyword::Sign,
+        Keyword::Tabf,
+        Keyword::Tabl,
+        Keyword::Uned,
+        Keyword::Unkn,
+        Keyword::Walk,
+        Keyword::Word,
+    ];
+
+    /// Iterate over [`Keyword::ALL`].
+    pub fn iter() -> impl Iterator<Item = Keyword> {
+        Self::ALL.iter().cloned()
+    }
+
+    /// Position of the keyword in a [`KeywordSet`], or `None` for
+    /// [`Keyword::Other`].
+    const fn bit(&self) -> Option<u32> {
+        Some(match self {
+            Keyword::Allocated => 0,
+            Keyword::Base => 1,
+            Keyword::Bref => 2,
+            Keyword::Changed => 3,
+            Keyword::Cofr => 4,
+            Keyword::Cons => 5,
+            Keyword::Core => 6,
+            Keyword::Dead => 7,
+            Keyword::Dumb => 8,
+            Keyword::Dupe => 9,
+            Keyword::Easy => 10,
+            Keyword::Eigen => 11,
+            Keyword::Fini => 12,
+            Keyword::Frac => 13,
+            Keyword::Full => 14,
+            Keyword::Hard => 15,
+            Keyword::Hear => 16,
+            Keyword::Less => 17,
+            Keyword::Look => 18,
+            Keyword::More => 19,
+            Keyword::Mult => 20,
+            Keyword::New => 21,
+            Keyword::Nice => 22,
+            Keyword::Nonn => 23,
+            Keyword::Obsc => 24,
+            Keyword::Probation => 25,
+            Keyword::Recycled => 26,
+            Keyword::Sign => 27,
+            Keyword::Tabf => 28,
+            Keyword::Tabl => 29,
+            Keyword::Uned => 30,
+            Keyword::Unkn => 31,
+            Keyword::Walk => 32,
+            Keyword::Word => 33,
+            Keyword::Other(_) => return None,
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
         match self {
+            Keyword::Allocated => "allocated",
             Keyword::Base => "base",
             Keyword::Bref => "bref",
             Keyword::Changed => "changed",
@@ -100,6 +200,7 @@ impl Keyword {
             Keyword::Nonn => "nonn",
             Keyword::Obsc => "obsc",
             Keyword::Probation => "probation",
+            Keyword::Recycled => "recycled",
             Keyword::Sign => "sign",
             Keyword::Tabf => "tabf",
             Keyword::Tabl => "tabl",
@@ -107,6 +208,7 @@ impl Keyword {
             Keyword::Unkn => "unkn",
             Keyword::Walk => "walk",
             Keyword::Word => "word",
+            Keyword::Other(s) => s,
         }
     }
 }
@@ -117,22 +219,157 @@ impl fmt::Display for Keyword {
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct ParseKeywordError(pub String);
+/// A set of keywords, stored as a bit set so that membership tests and set
+/// operations take constant time.
+///
+/// Only the keywords known to this crate can be stored: [`Keyword::Other`]
+/// is never contained in a set.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct KeywordSet(u64);
 
-impl fmt::Display for ParseKeywordError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "unknown OEIS keyword: {:?}", self.0)
+impl KeywordSet {
+    /// The empty set.
+    pub const EMPTY: Self = Self(0);
+
+    /// The set of the known keywords of `keywords`.
+    pub const fn from_keywords(keywords: &[Keyword]) -> Self {
+        let mut set = Self::EMPTY;
+        let mut i = 0;
+        while i < keywords.len() {
+            if let Some(bit) = keywords[i].bit() {
+                set.0 |= 1 << bit;
+            }
+            i += 1;
+        }
+        set
+    }
+
+    /// Add `keyword`. Returns `false` if it is [`Keyword::Other`], which
+    /// cannot be stored.
+    pub fn insert(&mut self, keyword: &Keyword) -> bool {
+        match keyword.bit() {
+            Some(bit) => {
+                self.0 |= 1 << bit;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Remove `keyword`.
+    pub fn remove(&mut self, keyword: &Keyword) {
+        if let Some(bit) = keyword.bit() {
+            self.0 &= !(1 << bit);
+        }
+    }
+
+    pub fn contains(&self, keyword: &Keyword) -> bool {
+        keyword.bit().is_some_and(|bit| self.0 & (1 << bit) != 0)
+    }
+
+    /// Whether the two sets have a keyword in common.
+    pub const fn intersects(self, other: Self) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    /// Whether all the keywords of `other` are in this set.
+    pub const fn contains_all(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    pub const fn intersection(self, other: Self) -> Self {
+        Self(self.0 & other.0)
+    }
+
+    /// The keywords of this set that are not in `other`.
+    pub const fn difference(self, other: Self) -> Self {
+        Self(self.0 & !other.0)
+    }
+
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub const fn len(self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    /// Iterate over the keywords of the set, in the order of
+    /// [`Keyword::ALL`].
+    pub fn iter(self) -> impl Iterator<Item = Keyword> {
+        Keyword::iter().filter(move |keyword| self.contains(keyword))
+    }
+}
+
+impl BitOr for KeywordSet {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        self.union(other)
+    }
+}
+
+impl BitAnd for KeywordSet {
+    type Output = Self;
+
+    fn bitand(self, other: Self) -> Self {
+        self.intersection(other)
     }
 }
 
-impl std::error::Error for ParseKeywordError {}
+impl Sub for KeywordSet {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self.difference(other)
+    }
+}
+
+impl<'a> FromIterator<&'a Keyword> for KeywordSet {
+    fn from_iter<I: IntoIterator<Item = &'a Keyword>>(iter: I) -> Self {
+        let mut set = Self::EMPTY;
+        for keyword in iter {
+            set.insert(keyword);
+        }
+        set
+    }
+}
+
+impl FromIterator<Keyword> for KeywordSet {
+    fn from_iter<I: IntoIterator<Item = Keyword>>(iter: I) -> Self {
+        let mut set = Self::EMPTY;
+        for keyword in iter {
+            set.insert(&keyword);
+        }
+        set
+    }
+}
+
+impl Serialize for Keyword {
+    /// Serialized as its name in the OEIS, e.g. `"nonn"`.
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Keyword {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let Ok(keyword) = String::deserialize(deserializer)?.parse();
+        Ok(keyword)
+    }
+}
 
 impl FromStr for Keyword {
-    type Err = ParseKeywordError;
+    type Err = Infallible;
 
+    /// Unknown keywords are parsed as [`Keyword::Other`].
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
+            "allocated" => Ok(Keyword::Allocated),
             "base" => Ok(Keyword::Base),
             "bref" => Ok(Keyword::Bref),
             "changed" => Ok(Keyword::Changed),
@@ -158,6 +395,7 @@ impl FromStr for Keyword {
             "nonn" => Ok(Keyword::Nonn),
             "obsc" => Ok(Keyword::Obsc),
             "probation" => Ok(Keyword::Probation),
+            "recycled" => Ok(Keyword::Recycled),
             "sign" => Ok(Keyword::Sign),
             "tabf" => Ok(Keyword::Tabf),
             "tabl" => Ok(Keyword::Tabl),
@@ -165,31 +403,190 @@ impl FromStr for Keyword {
             "unkn" => Ok(Keyword::Unkn),
             "walk" => Ok(Keyword::Walk),
             "word" => Ok(Keyword::Word),
-            other => Err(ParseKeywordError(other.to_owned())),
+            other => Ok(Keyword::Other(other.to_owned())),
         }
     }
 }
 
-fn join_lines(v: Vec<String>) -> String {
-    v.join("\n")
+/// An A-number identifying an OEIS sequence.
+///
+/// It is displayed as on oeis.org, with its digits zero-padded to at least
+/// [`ANumber::MIN_DIGITS`] (e.g. `A000045`), and in full beyond that (e.g.
+/// `A1000000`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ANumber(pub u64);
+
+impl ANumber {
+    /// Minimum number of digits of a formatted A-number.
+    pub const MIN_DIGITS: usize = 6;
+
+    /// URL of the sequence page on oeis.org.
+    pub fn url(self) -> String {
+        format!("https://oeis.org/{self}")
+    }
+
+    /// URL of the b-file of the sequence, e.g.
+    /// `https://oeis.org/A000045/b000045.txt`.
+    pub fn b_file_url(self) -> String {
+        format!(
+            "{}/b{:0width$}.txt",
+            self.url(),
+            self.0,
+            width = Self::MIN_DIGITS
+        )
+    }
 }
 
-fn parse_data(s: &str) -> Vec<BigInt> {
-    s.split(',')
-        .filter(|s| !s.is_empty())
-        .map(|s| s.parse().expect("invalid integer in OEIS data field"))
-        .collect()
+impl fmt::Display for ANumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "A{:0width$}", self.0, width = Self::MIN_DIGITS)
+    }
+}
+
+impl From<u64> for ANumber {
+    fn from(n: u64) -> Self {
+        ANumber(n)
+    }
+}
+
+impl From<ANumber> for u64 {
+    fn from(a: ANumber) -> Self {
+        a.0
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ParseANumberError(pub String);
+
+impl fmt::Display for ParseANumberError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid A-number: {:?}", self.0)
+    }
+}
+
+impl std::error::Error for ParseANumberError {}
+
+impl FromStr for ANumber {
+    type Err = ParseANumberError;
+
+    /// Parse an `A` followed by at least [`ANumber::MIN_DIGITS`] digits.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseANumberError(s.to_owned());
+        let digits = s.strip_prefix('A').ok_or_else(err)?;
+        if digits.len() < Self::MIN_DIGITS || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(err());
+        }
+        digits.parse().map(ANumber).map_err(|_| err())
+    }
+}
+
+/// The offset of a sequence, e.g. `0,5` for a sequence whose first term is
+/// a(0) and whose first term greater than 1 in absolute value is the 5th.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Offset {
+    first_index: i64,
+    first_term_gt_one: usize,
+}
+
+impl Offset {
+    pub fn new(first_index: i64, first_term_gt_one: usize) -> Self {
+        Self {
+            first_index,
+            first_term_gt_one,
+        }
+    }
+
+    /// Index of the first term, e.g. 0 if the sequence starts with a(0).
+    pub fn first_index(self) -> i64 {
+        self.first_index
+    }
+
+    /// Position (starting from 1) of the first term whose absolute value
+    /// is greater than 1, or 1 if there is none.
+    pub fn first_term_gt_one(self) -> usize {
+        self.first_term_gt_one
+    }
+}
+
+impl fmt::Display for Offset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.first_index, self.first_term_gt_one)
+    }
+}
+
+impl Serialize for Offset {
+    /// Serialized as in the OEIS, e.g. `"0,5"`.
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
 }
 
-fn parse_keywords(s: &str) -> Vec<Keyword> {
+#[derive(Debug, Clone)]
+pub struct ParseOffsetError(pub String);
+
+impl fmt::Display for ParseOffsetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid offset: {:?}", self.0)
+    }
+}
+
+impl std::error::Error for ParseOffsetError {}
+
+impl FromStr for Offset {
+    type Err = ParseOffsetError;
+
+    /// Parse an offset such as `0,5`. The second part is optional and
+    /// defaults to 1.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseOffsetError(s.to_owned());
+        let (first, second) = s.split_once(',').unwrap_or((s, "1"));
+        Ok(Self {
+            first_index: first.trim().parse().map_err(|_| err())?,
+            first_term_gt_one: second.trim().parse().map_err(|_| err())?,
+        })
+    }
+}
+
+/// Parse the comma-separated items of `s`, failing with the first item
+/// that cannot be parsed.
+fn parse_list<T: FromStr>(s: &str) -> Result<Vec<T>, &str> {
     s.split(',')
         .filter(|s| !s.is_empty())
-        .map(|s| s.parse().expect("unknown OEIS keyword"))
+        .map(|s| s.parse().map_err(|_| s))
         .collect()
 }
 
-/// An OEIS sequence in a form convenient for manipulation.
+/// Failure to convert an [`OeisEntry`] into an [`OeisSequence`] because one
+/// of its fields is malformed.
 #[derive(Debug, Clone)]
+pub struct ConvertError {
+    /// The A-number of the entry.
+    pub number: u64,
+    /// Name of the malformed field (e.g. `"data"`).
+    pub field: &'static str,
+    /// The malformed value.
+    pub value: String,
+}
+
+impl fmt::Display for ConvertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid {} in sequence {}: {:?}",
+            self.field,
+            ANumber(self.number),
+            self.value
+        )
+    }
+}
+
+impl std::error::Error for ConvertError {}
+
+/// An OEIS sequence in a form convenient for manipulation.
+///
+/// Serialized with its terms as decimal strings, since they may not fit in
+/// the numbers of the target format.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 #[allow(dead_code)]
 pub struct OeisSequence {
     /// The A-number (e.g. 250000 for A250000).
@@ -197,35 +594,38 @@ pub struct OeisSequence {
     /// Old-style handbook ID (e.g. "M0692 N0256").
     pub id: Option<String>,
     /// The sequence values.
+    #[serde(serialize_with = "serialize_terms")]
     pub data: Vec<BigInt>,
     /// Human-readable name/description.
     pub name: String,
-    /// Commentary and observations.
-    pub comment: String,
+    /// Commentary and observations. Text fields hold one entry per line of
+    /// the sequence page.
+    pub comment: Vec<String>,
     /// Bibliographic references.
-    pub reference: String,
+    pub reference: Vec<String>,
     /// URLs and resource links.
-    pub link: String,
+    pub link: Vec<String>,
     /// Mathematical formulas and bounds.
-    pub formula: String,
+    pub formula: Vec<String>,
     /// Worked examples.
-    pub example: String,
+    pub example: Vec<String>,
     /// Maple code.
-    pub maple: String,
+    pub maple: Vec<String>,
     /// Mathematica code.
-    pub mathematica: String,
+    pub mathematica: Vec<String>,
     /// Programs in other languages (PARI, Python, etc.).
-    pub program: String,
+    pub program: Vec<String>,
     /// Cross-references to related sequences.
-    pub xref: String,
+    pub xref: Vec<String>,
     /// Keyword tags (e.g. [Nonn, Core, Nice, Hard]).
     pub keyword: Vec<Keyword>,
-    /// Offset information (e.g. "0,5").
-    pub offset: String,
+    /// Index of the first term, and position of the first term greater
+    /// than 1.
+    pub offset: Offset,
     /// Author attribution.
     pub author: String,
     /// Extension and edit history.
-    pub ext: String,
+    pub ext: Vec<String>,
     /// Number of references.
     pub references: u64,
     /// Revision number.
@@ -234,32 +634,386 @@ pub struct OeisSequence {
     pub time: String,
     /// Creation timestamp (ISO 8601).
     pub created: String,
+    /// Where and when the sequence was fetched, if it comes from oeis.org.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
 }
 
-impl From<OeisEntry> for OeisSequence {
-    fn from(e: OeisEntry) -> Self {
+/// License of the OEIS content, to be credited along with any data taken
+/// from it.
+pub const OEIS_LICENSE: &str = "CC BY-SA 4.0";
+
+/// Where and when a sequence was obtained, so that exported data can be
+/// attributed to the OEIS.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Provenance {
+    /// URL of the sequence page (e.g. `https://oeis.org/A250000`).
+    pub source_url: String,
+    /// When the sequence was fetched.
+    pub fetched_at: SystemTime,
+    /// Revision number of the sequence at that time.
+    pub revision: u64,
+    /// License of the sequence data, see [`OEIS_LICENSE`].
+    pub license: String,
+}
+
+impl Provenance {
+    /// Provenance of revision `revision` of sequence `number`, fetched from
+    /// oeis.org at `fetched_at`.
+    pub fn new(number: u64, revision: u64, fetched_at: SystemTime) -> Self {
         Self {
+            source_url: ANumber(number).url(),
+            fetched_at,
+            revision,
+            license: OEIS_LICENSE.to_owned(),
+        }
+    }
+}
+
+impl OeisSequence {
+    /// Record that the sequence was fetched from oeis.org at `fetched_at`.
+    pub(crate) fn fetched_at(mut self, fetched_at: SystemTime) -> Self {
+        self.provenance = Some(Provenance::new(self.number, self.revision, fetched_at));
+        self
+    }
+
+    /// The keywords of `keyword` as a set, for fast filtering. Unknown
+    /// keywords are left out.
+    ///
+    /// The set is built from `keyword` on each call, so that it reflects any
+    /// change to it; keep the result when testing it several times.
+    pub fn keyword_set(&self) -> KeywordSet {
+        self.keyword.iter().collect()
+    }
+
+    /// Whether the sequence is tagged with `keyword`.
+    pub fn has_keyword(&self, keyword: &Keyword) -> bool {
+        self.keyword.contains(keyword)
+    }
+
+    /// Whether the sequence has only finitely many terms.
+    pub fn is_finite(&self) -> bool {
+        self.has_keyword(&Keyword::Fini)
+    }
+
+    /// Whether all the terms of the sequence are known and listed.
+    pub fn is_full(&self) -> bool {
+        self.has_keyword(&Keyword::Full)
+    }
+
+    /// Whether the sequence has negative terms.
+    pub fn is_signed(&self) -> bool {
+        self.has_keyword(&Keyword::Sign)
+    }
+
+    /// Whether the sequence is a triangle or an array read by rows or
+    /// antidiagonals.
+    pub fn is_table(&self) -> bool {
+        self.has_keyword(&Keyword::Tabl) || self.has_keyword(&Keyword::Tabf)
+    }
+
+    /// Whether the next term is not known and may be hard to find.
+    pub fn is_hard(&self) -> bool {
+        self.has_keyword(&Keyword::Hard)
+    }
+
+    /// Last modification time, or `None` if `time` is malformed.
+    #[cfg(feature = "chrono")]
+    pub fn modified_at(&self) -> Option<DateTime<FixedOffset>> {
+        DateTime::parse_from_rfc3339(&self.time).ok()
+    }
+
+    /// Creation time, or `None` if `created` is malformed.
+    #[cfg(feature = "chrono")]
+    pub fn created_at(&self) -> Option<DateTime<FixedOffset>> {
+        DateTime::parse_from_rfc3339(&self.created).ok()
+    }
+
+    /// Index of the first term in `data`.
+    pub fn first_index(&self) -> i64 {
+        self.offset.first_index()
+    }
+
+    /// Extend `data` with the terms of `bfile` that follow its last term.
+    /// Merging stops at the first gap in the b-file indices.
+    pub fn merge_bfile(&mut self, bfile: &BFile) {
+        let first = self.first_index();
+        let mut next = first + self.data.len() as i64;
+        for (n, value) in &bfile.terms {
+            if *n == next {
+                self.data.push(value.clone());
+                next += 1;
+            }
+        }
+    }
+
+    /// Cross-check the terms of `data` against `bfile`, returning the terms
+    /// that differ or are missing from the b-file.
+    pub fn verify_against_bfile(&self, bfile: &BFile) -> Vec<TermMismatch> {
+        let first = self.first_index();
+        let bfile_terms: HashMap<i64, &BigInt> = bfile.terms.iter().map(|(n, v)| (*n, v)).collect();
+        self.data
+            .iter()
+            .zip(first..)
+            .filter_map(|(value, index)| {
+                let expected = bfile_terms.get(&index).copied();
+                (expected != Some(value)).then(|| TermMismatch {
+                    index,
+                    data: value.clone(),
+                    bfile: expected.cloned(),
+                })
+            })
+            .collect()
+    }
+
+    /// The constant whose decimal expansion is given by the terms of a
+    /// [`Keyword::Cons`] sequence, e.g. `3.14159...` for the digits of pi.
+    ///
+    /// The decimal point is placed after the first [`Offset::first_index`]
+    /// digits. The expansion ends with `...` unless the sequence is finite.
+    /// Returns `None` if the sequence is not a decimal expansion or has terms
+    /// that are not digits.
+    pub fn decimal_expansion(&self) -> Option<String> {
+        if !self.has_keyword(&Keyword::Cons) || self.data.is_empty() {
+            return None;
+        }
+        let digits = self
+            .data
+            .iter()
+            .map(|term| match u8::try_from(term) {
+                Ok(d @ 0..=9) => Some(char::from(b'0' + d)),
+                _ => None,
+            })
+            .collect::<Option<String>>()?;
+        let point = self.first_index();
+        let mut expansion = if point <= 0 {
+            format!("0.{}{digits}", "0".repeat(point.unsigned_abs() as usize))
+        } else if point as usize >= digits.len() {
+            format!("{digits}{}", "0".repeat(point as usize - digits.len()))
+        } else {
+            let (int, frac) = digits.split_at(point as usize);
+            format!("{int}.{frac}")
+        };
+        if !self.is_finite() {
+            expansion.push_str("...");
+        }
+        Some(expansion)
+    }
+
+    /// The rows of a triangle or array read by rows, or `None` if the
+    /// sequence is not a table or its rows cannot be found.
+    ///
+    /// The rows of regular triangles ([`Keyword::Tabl`]) have 1, 2, 3, ...
+    /// terms. Those of irregular ones ([`Keyword::Tabf`]) are guessed from
+    /// the triangle drawn in the examples, where each line holds a row,
+    /// possibly preceded by its index. Only complete rows are returned.
+    pub fn rows(&self) -> Option<Vec<&[BigInt]>> {
+        let lengths = if self.has_keyword(&Keyword::Tabl) {
+            (1..)
+                .take_while(|n| n * (n + 1) / 2 <= self.data.len())
+                .collect()
+        } else if self.has_keyword(&Keyword::Tabf) {
+            example_row_lengths(&self.example, &self.data)
+        } else {
+            return None;
+        };
+        let mut rest = self.data.as_slice();
+        let rows: Vec<_> = lengths
+            .into_iter()
+            .map(|len| {
+                let (row, tail) = rest.split_at(len);
+                rest = tail;
+                row
+            })
+            .collect();
+        (!rows.is_empty()).then_some(rows)
+    }
+}
+
+/// Maximum length of the label before a `:` on a line of the examples,
+/// e.g. `n=3` or `T(3,k)` in `T(3,k): 1, 3, 3, 1`.
+const ROW_LABEL_LEN: usize = 12;
+
+/// The lengths of the rows of `data` drawn in `example`, one per line, in
+/// order. Row labels (see [`strip_row_label`]) are skipped, and lines that
+/// do not continue `data` are ignored.
+fn example_row_lengths(example: &[String], data: &[BigInt]) -> Vec<usize> {
+    let mut lengths = Vec::new();
+    let mut pos = 0;
+    for line in example {
+        let row = integers(strip_row_label(line));
+        if !row.is_empty() && data[pos..].starts_with(&row) {
+            lengths.push(row.len());
+            pos += row.len();
+        }
+    }
+    lengths
+}
+
+/// `line` without its row label, if any: a short prefix ending with `:`
+/// (e.g. `n=3:` or `3:`), or an index followed by `=` (e.g. `n=3` or
+/// `3 =`).
+fn strip_row_label(line: &str) -> &str {
+    let line = line.trim_start();
+    if let Some((label, row)) = line.split_once(':')
+        && label.len() <= ROW_LABEL_LEN
+        && !label.contains(';')
+    {
+        return row;
+    }
+    let is_digit = |c: char| c.is_ascii_digit();
+    if let Some(rest) = line
+        .strip_prefix('n')
+        .and_then(|rest| rest.trim_start().strip_prefix('='))
+    {
+        return rest.trim_start().trim_start_matches(is_digit);
+    }
+    let rest = line.trim_start_matches(is_digit);
+    if rest.len() < line.len()
+        && let Some(row) = rest.trim_start().strip_prefix('=')
+    {
+        return row;
+    }
+    line
+}
+
+/// The integers in `line`, e.g. 1, 3, 3 and 1 in `1, 3, 3, 1`. A `-` is a
+/// sign unless it follows a letter, a digit or a closing parenthesis, as in
+/// `n-1`.
+fn integers(line: &str) -> Vec<BigInt> {
+    let mut numbers = Vec::new();
+    let mut pos = 0;
+    while let Some(offset) = line[pos..].find(|c: char| c.is_ascii_digit()) {
+        let start = pos + offset;
+        let len = line[start..]
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(line.len() - start);
+        let negative = line[..start].ends_with('-')
+            && !line[..start - 1].ends_with(|c: char| c.is_alphanumeric() || c == ')');
+        let number = &line[start - usize::from(negative)..start + len];
+        numbers.push(number.parse().expect("digits form an integer"));
+        pos = start + len;
+    }
+    numbers
+}
+
+fn serialize_terms<S: Serializer>(terms: &[BigInt], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(terms.iter().map(BigInt::to_string))
+}
+
+/// Number of terms shown by the [`Display`](fmt::Display) implementation
+/// of [`OeisSequence`].
+const DISPLAY_TERMS: usize = 20;
+
+impl fmt::Display for OeisSequence {
+    /// A short summary of the sequence, e.g.
+    ///
+    /// ```text
+    /// A000045 Fibonacci numbers: F(n) = F(n-1) + F(n-2) with F(0) = 0 and F(1) = 1.
+    /// Terms: 0, 1, 1, 2, 3, 5, 8, 13, 21, 34, 55, 89, 144, 233, 377, 610, 987, 1597, 2584, 4181, ...
+    /// Offset: 0,4
+    /// Keywords: core,nonn,nice,easy,hear,changed
+    /// Author: _N. J. A. Sloane_, 1964
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} {}", ANumber(self.number), self.name)?;
+        f.write_str("Terms:")?;
+        for (i, term) in self.data.iter().take(DISPLAY_TERMS).enumerate() {
+            write!(f, "{}{term}", if i == 0 { " " } else { ", " })?;
+        }
+        if self.data.len() > DISPLAY_TERMS {
+            f.write_str(", ...")?;
+        }
+        writeln!(f)?;
+        writeln!(f, "Offset: {}", self.offset)?;
+        f.write_str("Keywords: ")?;
+        for (i, keyword) in self.keyword.iter().enumerate() {
+            write!(f, "{}{keyword}", if i == 0 { "" } else { "," })?;
+        }
+        writeln!(f)?;
+        write!(f, "Author: {}", self.author)
+    }
+}
+
+impl TryFrom<OeisEntry> for OeisSequence {
+    type Error = ConvertError;
+
+    fn try_from(e: OeisEntry) -> Result<Self, Self::Error> {
+        let err = |field, value: &str| ConvertError {
+            number: e.number,
+            field,
+            value: value.to_owned(),
+        };
+        let data = parse_list(&e.data).map_err(|v| err("data", v))?;
+        let keyword = parse_list(&e.keyword).map_err(|v| err("keyword", v))?;
+        let offset = e.offset.parse().map_err(|_| err("offset", &e.offset))?;
+        Ok(Self {
             number: e.number,
             id: e.id,
-            data: parse_data(&e.data),
+            data,
             name: e.name,
-            comment: join_lines(e.comment),
-            reference: join_lines(e.reference),
-            link: join_lines(e.link),
-            formula: join_lines(e.formula),
-            example: join_lines(e.example),
-            maple: join_lines(e.maple),
-            mathematica: join_lines(e.mathematica),
-            program: join_lines(e.program),
-            xref: join_lines(e.xref),
-            keyword: parse_keywords(&e.keyword),
-            offset: e.offset,
+            comment: e.comment,
+            reference: e.reference,
+            link: e.link,
+            formula: e.formula,
+            example: e.example,
+            maple: e.maple,
+            mathematica: e.mathematica,
+            program: e.program,
+            xref: e.xref,
+            keyword,
+            offset,
             author: e.author,
-            ext: join_lines(e.ext),
+            ext: e.ext,
             references: e.references,
             revision: e.revision,
             time: e.time,
             created: e.created,
+            provenance: None,
+        })
+    }
+}
+
+/// A page of search results in the JSON format.
+///
+/// The API returns a bare array of entries, or `null` when nothing
+/// matches. Older versions wrapped the entries in an object along with the
+/// total number of matches, which is accepted as well.
+#[derive(Debug)]
+pub(crate) enum SearchPage {
+    Entries(Option<Vec<OeisEntry>>),
+    Envelope {
+        results: Option<Vec<OeisEntry>>,
+        count: Option<usize>,
+    },
+}
+
+/// The object wrapping the entries in [`SearchPage::Envelope`].
+#[derive(Deserialize)]
+struct Envelope {
+    results: Option<Vec<OeisEntry>>,
+    count: Option<usize>,
+}
+
+impl SearchPage {
+    /// Parse a page from the JSON returned by the API.
+    ///
+    /// The format is told from the first token, so that errors in entries
+    /// are reported as such.
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        if json.trim_start().starts_with('{') {
+            let Envelope { results, count } = serde_json::from_str(json)?;
+            Ok(SearchPage::Envelope { results, count })
+        } else {
+            serde_json::from_str(json).map(SearchPage::Entries)
+        }
+    }
+
+    /// The entries of the page, and the total number of matches if known.
+    pub fn into_parts(self) -> (Vec<OeisEntry>, Option<usize>) {
+        match self {
+            SearchPage::Entries(entries) => (entries.unwrap_or_default(), None),
+            SearchPage::Envelope { results, count } => (results.unwrap_or_default(), count),
         }
     }
 }
@@ -268,7 +1022,7 @@ impl From<OeisEntry> for OeisSequence {
 ///
 /// The API response is a `Vec<OeisEntry>`. Convert to [`OeisSequence`] for
 /// easier manipulation.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize)]
 pub struct OeisEntry {
     /// The A-number (e.g. 250000 for A250000).
     pub number: u64,
@@ -346,3 +1100,140 @@ pub struct OeisEntry {
     /// Creation timestamp (ISO 8601).
     pub created: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::internal::parse_internal;
+
+    /// A sequence parsed from the OEIS internal format.
+    fn sequence(text: &str) -> OeisSequence {
+        OeisSequence::try_from(parse_internal(text).unwrap().remove(0)).unwrap()
+    }
+
+    fn terms(terms: &[i64]) -> Vec<BigInt> {
+        terms.iter().map(|&t| BigInt::from(t)).collect()
+    }
+
+    fn lines(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|&line| line.to_owned()).collect()
+    }
+
+    fn rows(seq: &OeisSequence) -> Option<Vec<Vec<i64>>> {
+        let rows = seq.rows()?;
+        Some(
+            rows.into_iter()
+                .map(|row| row.iter().map(|t| i64::try_from(t).unwrap()).collect())
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn rows_tabl() {
+        let seq = sequence(
+            "%S A007318 1,1,1,1,2,1,1,3,3,1\n%N A007318 Pascal's triangle.\n%O A007318 0,5\n%K A007318 nonn,tabl\n",
+        );
+        assert_eq!(
+            rows(&seq),
+            Some(vec![vec![1], vec![1, 1], vec![1, 2, 1], vec![1, 3, 3, 1]])
+        );
+    }
+
+    #[test]
+    fn rows_tabl_incomplete_row() {
+        let seq = sequence(
+            "%S A007318 1,1,1,1,2,1,1,3\n%N A007318 Pascal's triangle.\n%O A007318 0,5\n%K A007318 nonn,tabl\n",
+        );
+        assert_eq!(rows(&seq), Some(vec![vec![1], vec![1, 1], vec![1, 2, 1]]));
+    }
+
+    #[test]
+    fn rows_tabf_with_labels() {
+        let seq = sequence(
+            "%S A000001 1,1,1,1,2,1\n%N A000001 A triangle.\n\
+             %e A000001 Triangle T(n,k) for n >= 0 and 0 <= k <= n-1 begins:\n\
+             %e A000001 n=0: 1\n%e A000001 n=1: 1, 1\n%e A000001 n=2: 1, 2, 1\n\
+             %O A000001 0,5\n%K A000001 nonn,tabf\n",
+        );
+        assert_eq!(rows(&seq), Some(vec![vec![1], vec![1, 1], vec![1, 2, 1]]));
+    }
+
+    #[test]
+    fn rows_tabf_without_labels() {
+        let seq = sequence(
+            "%S A000001 1,1,-1,1,2,-2,1\n%N A000001 A signed triangle.\n\
+             %e A000001 Triangle begins:\n%e A000001   1;\n%e A000001   1, -1;\n\
+             %e A000001   1,  2, -2, 1;\n%O A000001 0,4\n%K A000001 sign,tabf\n",
+        );
+        assert_eq!(
+            rows(&seq),
+            Some(vec![vec![1], vec![1, -1], vec![1, 2, -2, 1]])
+        );
+    }
+
+    #[test]
+    fn rows_not_a_table() {
+        let seq = sequence(
+            "%S A000045 0,1,1,2,3,5\n%N A000045 Fibonacci numbers.\n%O A000045 0,4\n%K A000045 core,nonn\n",
+        );
+        assert_eq!(seq.rows(), None);
+    }
+
+    #[test]
+    fn example_row_lengths_labels() {
+        let data = terms(&[1, 1, 1, 1, 2, 1, 1, 3, 3, 1]);
+        let cases: &[(&[&str], &[usize])] = &[
+            (&["n=0: 1", "n=1: 1, 1", "n=2: 1, 2, 1"], &[1, 2, 3]),
+            (&["0: 1", "1: 1 1", "2: 1 2 1", "3: 1 3 3 1"], &[1, 2, 3, 4]),
+            (&["n = 0   1", "n = 1   1  1"], &[1, 2]),
+            (&["0 = 1", "1 = 1 + 1"], &[1, 2]),
+            (&["T(0,k): 1", "T(1,k): 1, 1"], &[1, 2]),
+            (&["1;", "1, 1;", "1, 2, 1;"], &[1, 2, 3]),
+        ];
+        for &(example, expected) in cases {
+            assert_eq!(
+                example_row_lengths(&lines(example), &data),
+                expected,
+                "{example:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn example_row_lengths_data_ends_within_row() {
+        let data = terms(&[1, 1, 1, 1, 2]);
+        let example = lines(&["1;", "1, 1;", "1, 2, 1;", "1, 3, 3, 1;"]);
+        assert_eq!(example_row_lengths(&example, &data), [1, 2]);
+    }
+
+    #[test]
+    fn integers_signs() {
+        assert_eq!(integers("1, -2, 3"), terms(&[1, -2, 3]));
+        assert_eq!(integers("T(n-1,k) = 3"), terms(&[1, 3]));
+        assert_eq!(integers("a(n)-1 and 2-1"), terms(&[1, 2, 1]));
+    }
+
+    #[test]
+    fn decimal_expansion() {
+        let cases = [
+            ("1,4,1,4,2,1", "1,1", "cons,nonn", Some("1.41421...")),
+            ("1,2,3", "0,1", "cons,nonn", Some("0.123...")),
+            ("1,2,3", "-2,1", "cons,nonn", Some("0.00123...")),
+            ("1,2,3", "5,1", "cons,nonn", Some("12300...")),
+            ("1,2,5", "0,1", "cons,fini,nonn", Some("0.125")),
+            ("1,2,5", "3,1", "cons,fini,nonn", Some("125")),
+            ("1,12,5", "1,2", "cons,nonn", None),
+            ("1,2,5", "1,1", "nonn", None),
+        ];
+        for (data, offset, keywords, expected) in cases {
+            let seq = sequence(&format!(
+                "%S A000001 {data}\n%N A000001 A constant.\n%O A000001 {offset}\n%K A000001 {keywords}\n"
+            ));
+            assert_eq!(
+                seq.decimal_expansion().as_deref(),
+                expected,
+                "{data} {offset} {keywords}"
+            );
+        }
+    }
+}
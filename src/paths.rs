@@ -0,0 +1,42 @@
+use directories::ProjectDirs;
+use std::path::{Path, PathBuf};
+
+/// Directories where the bot keeps its files, following the conventions of
+/// the platform (e.g. `~/.cache/oeis_bot` for the cache on Linux) unless
+/// they are all placed under a single directory with [`Paths::in_dir`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Paths {
+    /// Configuration files.
+    pub config_dir: PathBuf,
+    /// Files that can be deleted at any time, e.g. cached responses.
+    pub cache_dir: PathBuf,
+    /// Files that should persist but are not worth backing up, e.g. logs.
+    pub state_dir: PathBuf,
+    /// Files that should persist, e.g. the post history.
+    pub data_dir: PathBuf,
+}
+
+impl Paths {
+    /// The platform's directories for the bot, or `None` if they cannot be
+    /// determined (e.g. no home directory).
+    pub fn platform() -> Option<Self> {
+        let dirs = ProjectDirs::from("", "", env!("CARGO_PKG_NAME"))?;
+        Some(Self {
+            config_dir: dirs.config_dir().to_owned(),
+            cache_dir: dirs.cache_dir().to_owned(),
+            state_dir: dirs.state_dir().unwrap_or(dirs.data_local_dir()).to_owned(),
+            data_dir: dirs.data_dir().to_owned(),
+        })
+    }
+
+    /// Subdirectories of `root`, e.g. `root/cache` for the cache.
+    pub fn in_dir(root: impl AsRef<Path>) -> Self {
+        let root = root.as_ref();
+        Self {
+            config_dir: root.join("config"),
+            cache_dir: root.join("cache"),
+            state_dir: root.join("state"),
+            data_dir: root.join("data"),
+        }
+    }
+}
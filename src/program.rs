@@ -0,0 +1,55 @@
+use crate::oeis::OeisSequence;
+
+/// A program from the program section of an OEIS entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Program {
+    /// The language, as given by the prefix of the first line (e.g. `PARI`
+    /// for `(PARI)`). Empty for lines before the first prefix.
+    pub language: String,
+    /// The code, possibly spanning several lines, without the prefix.
+    pub code: String,
+}
+
+/// Longest language prefix, e.g. `(Common Lisp)`.
+const MAX_LANGUAGE_LEN: usize = 30;
+
+/// The programs of `seq`, in order. A program starts at each line with a
+/// language prefix such as `(PARI)` or `(Python)`, and continues until the
+/// next one.
+pub fn programs(seq: &OeisSequence) -> Vec<Program> {
+    let mut programs: Vec<Program> = Vec::new();
+    for line in &seq.program {
+        match language_prefix(line) {
+            Some((language, code)) => programs.push(Program {
+                language: language.to_owned(),
+                code: code.trim_start().to_owned(),
+            }),
+            None => match programs.last_mut() {
+                Some(program) => {
+                    program.code.push('\n');
+                    program.code.push_str(line);
+                }
+                None => programs.push(Program {
+                    language: String::new(),
+                    code: line.to_owned(),
+                }),
+            },
+        }
+    }
+    programs
+}
+
+/// Split a line starting with a language prefix into the language and the
+/// rest of the line. Code that merely starts with a parenthesis (e.g.
+/// `(define ...)` in Scheme) is not a prefix, as languages start with an
+/// uppercase letter.
+fn language_prefix(line: &str) -> Option<(&str, &str)> {
+    let rest = line.strip_prefix('(')?;
+    let end = rest.find(')').filter(|end| *end <= MAX_LANGUAGE_LEN)?;
+    let language = &rest[..end];
+    let valid = language.starts_with(|c: char| c.is_ascii_uppercase())
+        && language
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || " /+#.-".contains(c));
+    valid.then(|| (language, &rest[end + 1..]))
+}
@@ -0,0 +1,299 @@
+use crate::oeis::{ANumber, Keyword};
+use num_bigint::BigInt;
+use std::fmt;
+use std::str::FromStr;
+
+/// Field prefixes recognised by the OEIS search, e.g. `author:` in
+/// `author:Sloane`.
+const FIELDS: &[&str] = &[
+    "author",
+    "comment",
+    "example",
+    "extension",
+    "formula",
+    "id",
+    "keyword",
+    "link",
+    "maple",
+    "mathematica",
+    "name",
+    "offset",
+    "program",
+    "ref",
+    "seq",
+    "signed",
+    "signedsubs",
+    "subs",
+    "xref",
+];
+
+/// A search query in the OEIS query syntax, built from individual clauses.
+///
+/// Clauses are combined with AND. See the
+/// [documentation](https://oeis.org/hints.html) for details.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Query {
+    clauses: Vec<String>,
+    sort: Option<Sort>,
+}
+
+/// Order of search results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sort {
+    /// Best matches first, the default.
+    Relevance,
+    /// Most referenced sequences first.
+    References,
+    /// Increasing A-number.
+    Number,
+    /// Most recently modified first.
+    Modified,
+    /// Most recently created first.
+    Created,
+}
+
+impl Sort {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Sort::Relevance => "relevance",
+            Sort::References => "references",
+            Sort::Number => "number",
+            Sort::Modified => "modified",
+            Sort::Created => "created",
+        }
+    }
+}
+
+impl Query {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Match a single sequence by its A-number.
+    pub fn id(self, id: u64) -> Self {
+        self.clause(format!("id:{}", ANumber(id)))
+    }
+
+    /// Match a sequence by its old-style handbook ID (e.g. "M0692" or
+    /// "N0256").
+    pub fn handbook_id(self, id: &str) -> Self {
+        self.clause(format!("id:{id}"))
+    }
+
+    /// Match any of several sequences by their A-numbers.
+    pub fn ids(self, ids: &[u64]) -> Self {
+        let ids: Vec<String> = ids.iter().map(|id| ANumber(*id).to_string()).collect();
+        self.clause(format!("id:{}", ids.join("|")))
+    }
+
+    /// Match sequences tagged with the given keyword.
+    pub fn keyword(self, keyword: Keyword) -> Self {
+        self.clause(format!("keyword:{keyword}"))
+    }
+
+    /// Match sequences not tagged with the given keyword.
+    pub fn without_keyword(self, keyword: Keyword) -> Self {
+        self.clause(format!("-keyword:{keyword}"))
+    }
+
+    /// Match sequences whose author field contains `author`.
+    pub fn author(self, author: &str) -> Self {
+        self.clause(format!("author:{}", quote(author)))
+    }
+
+    /// Match sequences whose name contains `words`.
+    pub fn name(self, words: &str) -> Self {
+        self.clause(format!("name:{}", quote(words)))
+    }
+
+    /// Match sequences containing the given terms, in order and adjacent.
+    pub fn terms(self, terms: &[BigInt]) -> Self {
+        let terms: Vec<String> = terms.iter().map(|n| n.to_string()).collect();
+        self.clause(terms.join(","))
+    }
+
+    /// Parse a whole query in the OEIS syntax (e.g. `keyword:nice
+    /// author:Sloane seq:1,2,3`), checking that each clause uses a known
+    /// field and keyword, so that typos are caught before sending it.
+    pub fn parse(s: &str) -> Result<Self, ParseQueryError> {
+        let mut query = Self::new();
+        for clause in split_clauses(s)? {
+            // A colon only introduces a field before any quote, so that
+            // quoted phrases such as `"foo:bar"` are searched as is.
+            let body = clause.trim_start_matches('-');
+            let (field, value) = match body.split_once(':') {
+                Some((field, value)) if !field.contains('"') => (Some(field), value),
+                _ => (None, body),
+            };
+            if let Some(field) = field
+                && !FIELDS.contains(&field)
+            {
+                return Err(ParseQueryError::UnknownField(field.to_owned()));
+            }
+            if value.trim_matches('"').is_empty() {
+                return Err(ParseQueryError::EmptyValue(clause));
+            }
+            if field == Some("keyword")
+                && let Ok(Keyword::Other(kw)) = value.parse()
+            {
+                return Err(ParseQueryError::UnknownKeyword(kw));
+            }
+            query = query.clause(clause);
+        }
+        Ok(query)
+    }
+
+    /// Add a raw clause, passed through to the OEIS unchanged.
+    pub fn raw(self, fragment: &str) -> Self {
+        self.clause(fragment.to_owned())
+    }
+
+    pub(crate) fn clauses(&self) -> &[String] {
+        &self.clauses
+    }
+
+    /// Order the results by `sort` instead of by relevance.
+    pub fn sort(mut self, sort: Sort) -> Self {
+        self.sort = Some(sort);
+        self
+    }
+
+    pub(crate) fn sort_order(&self) -> Option<Sort> {
+        self.sort
+    }
+
+    fn clause(mut self, clause: String) -> Self {
+        self.clauses.push(clause);
+        self
+    }
+}
+
+impl FromStr for Query {
+    type Err = ParseQueryError;
+
+    /// See [`Query::parse`].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+/// Error in a query parsed by [`Query::parse`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseQueryError {
+    /// A clause uses a field the OEIS does not know, e.g. `keywords:`.
+    UnknownField(String),
+    /// A `keyword:` clause names an unknown keyword.
+    UnknownKeyword(String),
+    /// A clause has no value, e.g. `author:`.
+    EmptyValue(String),
+    /// A double quote is not closed.
+    UnclosedQuote,
+}
+
+impl fmt::Display for ParseQueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseQueryError::UnknownField(field) => write!(f, "unknown search field {field:?}"),
+            ParseQueryError::UnknownKeyword(kw) => write!(f, "unknown keyword {kw:?}"),
+            ParseQueryError::EmptyValue(clause) => write!(f, "missing value in {clause:?}"),
+            ParseQueryError::UnclosedQuote => write!(f, "unclosed double quote"),
+        }
+    }
+}
+
+impl std::error::Error for ParseQueryError {}
+
+/// Split a query into whitespace-separated clauses, keeping quoted values
+/// (e.g. `author:"N. J. A. Sloane"`) whole.
+fn split_clauses(s: &str) -> Result<Vec<String>, ParseQueryError> {
+    let mut clauses = Vec::new();
+    let mut clause = String::new();
+    let mut quoted = false;
+    for c in s.chars() {
+        if c == '"' {
+            quoted = !quoted;
+        }
+        if c.is_whitespace() && !quoted {
+            if !clause.is_empty() {
+                clauses.push(std::mem::take(&mut clause));
+            }
+        } else {
+            clause.push(c);
+        }
+    }
+    if quoted {
+        return Err(ParseQueryError::UnclosedQuote);
+    }
+    if !clause.is_empty() {
+        clauses.push(clause);
+    }
+    Ok(clauses)
+}
+
+impl fmt::Display for Query {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.clauses.join(" "))
+    }
+}
+
+/// Wrap a value in double quotes if it contains whitespace, so that it is
+/// treated as a single clause.
+fn quote(s: &str) -> String {
+    if s.contains(char::is_whitespace) {
+        format!("\"{}\"", s.replace('"', ""))
+    } else {
+        s.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_round_trip() {
+        let queries = [
+            "keyword:nice author:Sloane seq:1,2,3",
+            "-keyword:dead name:\"Catalan numbers\"",
+            "author:\"N. J. A. Sloane\" id:A000045",
+            "\"foo:bar\"",
+            "name:\"ratio a:b\"",
+            "1,2,3,5,8 -4",
+        ];
+        for q in queries {
+            let query = Query::parse(q).unwrap_or_else(|e| panic!("{q:?}: {e}"));
+            assert_eq!(query.to_string(), q);
+        }
+    }
+
+    #[test]
+    fn parse_splits_clauses() {
+        let query = Query::parse("  keyword:nice   author:\"N. J. A. Sloane\" ").unwrap();
+        assert_eq!(
+            query,
+            Query::new()
+                .keyword(Keyword::Nice)
+                .author("N. J. A. Sloane")
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        let cases = [
+            (
+                "keywords:nice",
+                ParseQueryError::UnknownField("keywords".into()),
+            ),
+            (
+                "keyword:nicee",
+                ParseQueryError::UnknownKeyword("nicee".into()),
+            ),
+            ("author:", ParseQueryError::EmptyValue("author:".into())),
+            ("name:\"\"", ParseQueryError::EmptyValue("name:\"\"".into())),
+            ("name:\"Catalan", ParseQueryError::UnclosedQuote),
+        ];
+        for (q, expected) in cases {
+            assert_eq!(Query::parse(q), Err(expected), "{q:?}");
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use rand::Rng;
+use std::time::Duration;
+
+/// How failed requests to oeis.org are retried.
+///
+/// Transient errors (server errors, timeouts, connection failures) are
+/// retried up to `max_attempts` times in total. The delay before each retry
+/// is chosen uniformly at random up to an exponentially growing bound
+/// ("full jitter"), starting at `initial_backoff` and capped at
+/// `max_backoff`. When rate-limited, the delay asked by the server is used
+/// instead, unless it is longer than `max_backoff`, in which case the
+/// error is returned without retrying.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts, including the first one.
+    pub max_attempts: u32,
+    /// Upper bound of the delay before the first retry.
+    pub initial_backoff: Duration,
+    /// Upper bound of the delay before any retry.
+    pub max_backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// Never retry.
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Delay before retrying after `attempt` failed attempts.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        let bound = self
+            .initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff);
+        bound.mul_f64(rand::rng().random())
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(30),
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use crate::text;
+
+/// Maximum width of a single whitespace-separated token, in columns (see
+/// [`text::width`]). Longer tokens are cut and end with an ellipsis.
+const MAX_TOKEN_LEN: usize = 80;
+
+/// Zero-width space, inserted after `@` and `#` so that Mastodon does not
+/// turn them into mentions or hashtags.
+const ZWSP: char = '\u{200B}';
+
+/// Make OEIS-derived text safe to include in a post.
+///
+/// This strips control characters and HTML tags, shortens excessively long
+/// tokens, and neutralises mentions (`@admin`) and hashtags.
+pub fn sanitize(s: &str) -> String {
+    let s = strip_tags(s);
+    let s: String = s
+        .chars()
+        .filter(|c| *c == '\n' || !c.is_control())
+        .collect();
+    let s = s
+        .lines()
+        .map(|line| {
+            line.split(' ')
+                .map(shorten_token)
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    neutralize_mentions(&s)
+}
+
+/// Tags found in OEIS entries, and tags that could be used for injection,
+/// in lowercase. Other text between `<` and `>` is left untouched.
+const TAGS: &[&str] = &[
+    "a", "b", "big", "br", "center", "code", "div", "em", "font", "hr", "i", "iframe", "img", "li",
+    "ol", "p", "pre", "script", "small", "span", "strong", "style", "sub", "sup", "table", "td",
+    "th", "tr", "tt", "u", "ul",
+];
+
+/// Remove HTML tags, i.e. a known tag name after `<` or `</`, followed by
+/// `>`, `/>` or attributes up to the next `>`. Comparisons such as
+/// `a(n) < 2^n`, `a(k-1)<a(k)` or `a<b and c>d` are left untouched.
+fn strip_tags(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(i) = rest.find('<') {
+        out.push_str(&rest[..i]);
+        let after = &rest[i + 1..];
+        match tag_len(after) {
+            Some(len) => rest = &after[len..],
+            None => {
+                out.push('<');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Length of the tag starting `s` (just after its `<`), including the
+/// closing `>`, or `None` if `s` does not start with a tag.
+fn tag_len(s: &str) -> Option<usize> {
+    let name_start = usize::from(s.starts_with('/'));
+    let name_len = s[name_start..]
+        .find(|c: char| !c.is_ascii_alphanumeric())
+        .unwrap_or(s.len() - name_start);
+    let name = &s[name_start..name_start + name_len];
+    if !TAGS.iter().any(|tag| tag.eq_ignore_ascii_case(name)) {
+        return None;
+    }
+    let end = s.find('>')?;
+    let attributes = s[name_start + name_len..end].trim();
+    let attributes = attributes
+        .strip_suffix('/')
+        .unwrap_or(attributes)
+        .trim_end();
+    let is_tag = if attributes.is_empty() {
+        true
+    } else {
+        // Attributes must follow the name after whitespace, and start with
+        // `name=`.
+        let separated = s[name_start + name_len..].starts_with(char::is_whitespace);
+        let attr_len = attributes
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+            .unwrap_or(attributes.len());
+        separated && attr_len > 0 && attributes[attr_len..].trim_start().starts_with('=')
+    };
+    is_tag.then_some(end + 1)
+}
+
+fn shorten_token(token: &str) -> String {
+    text::truncate_with_ellipsis(token, MAX_TOKEN_LEN).into_owned()
+}
+
+fn neutralize_mentions(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        out.push(c);
+        if c == '@' || c == '#' {
+            out.push(ZWSP);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_inequalities() {
+        let cases = [
+            "Numbers k such that a(k-1)<a(k) and k>2.",
+            "a(n)<a(n+1) for n>2.",
+            "a(n) < 2^n for n > 1.",
+            "Numbers k such that a<b and c>d.",
+            "x<i and i>0",
+            "0<=k<=n",
+            "1 < p < q.",
+        ];
+        for case in cases {
+            assert_eq!(sanitize(case), case);
+        }
+    }
+
+    #[test]
+    fn strips_tags() {
+        let cases = [
+            (
+                "a(n)<a(n+1) for n>2. See <a href=\"/A000045\">A000045</a>.",
+                "a(n)<a(n+1) for n>2. See A000045.",
+            ),
+            (
+                "<a href=\"https://example.com/x.pdf\" target=\"_blank\" rel=\"nofollow\">Paper</a>",
+                "Paper",
+            ),
+            ("x<sup>2</sup> + y<SUB>n</SUB>", "x2 + yn"),
+            ("line<br/>break<br />here<BR>", "linebreakhere"),
+            ("<i>italic</i> and <b>bold</b>", "italic and bold"),
+            ("<script>alert(1)</script>", "alert(1)"),
+            (
+                "<blink>not a known tag</blink>",
+                "<blink>not a known tag</blink>",
+            ),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(sanitize(input), expected, "{input:?}");
+        }
+    }
+
+    #[test]
+    fn neutralizes_mentions_and_hashtags() {
+        assert_eq!(sanitize("ping @admin"), "ping @\u{200B}admin");
+        assert_eq!(
+            sanitize("cc @admin@mastodon.social"),
+            "cc @\u{200B}admin@\u{200B}mastodon.social"
+        );
+        assert_eq!(sanitize("#spam"), "#\u{200B}spam");
+    }
+
+    #[test]
+    fn strips_control_characters() {
+        assert_eq!(sanitize("a\u{7}b\r\nc\u{1B}[31m"), "ab\nc[31m");
+    }
+
+    #[test]
+    fn shortens_long_tokens() {
+        let long = "1".repeat(200);
+        let sanitized = sanitize(&format!("short {long}"));
+        assert!(sanitized.starts_with("short 111"));
+        assert!(text::width(&sanitized) <= "short ".len() + MAX_TOKEN_LEN);
+        assert!(sanitized.ends_with(text::ELLIPSIS));
+    }
+}
@@ -0,0 +1,270 @@
+use crate::mirror::{Mirror, MirrorEntry};
+use crate::oeis::{Keyword, OeisSequence};
+use num_bigint::BigInt;
+use rusqlite::{Connection, OptionalExtension, params, params_from_iter};
+use std::path::Path;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS sequences (
+    number INTEGER PRIMARY KEY,
+    name TEXT NOT NULL,
+    data TEXT NOT NULL,
+    terms INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS keywords (
+    number INTEGER NOT NULL,
+    keyword TEXT NOT NULL,
+    PRIMARY KEY (number, keyword)
+);
+CREATE TABLE IF NOT EXISTS posts (
+    number INTEGER NOT NULL,
+    posted_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+CREATE TABLE IF NOT EXISTS commentary (
+    number INTEGER PRIMARY KEY,
+    text TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS pool (
+    number INTEGER PRIMARY KEY,
+    status TEXT NOT NULL
+);
+";
+
+/// An SQLite database holding the local mirror, the keywords of fetched
+/// sequences, the history of posted sequences, commentary written by the
+/// operator, and a pool of statuses to post when the OEIS is unreachable.
+pub struct Store {
+    conn: Connection,
+}
+
+/// Criteria for selecting sequences from a [`Store`].
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    keywords: Vec<Keyword>,
+    excluded_keywords: Vec<Keyword>,
+    min_terms: Option<usize>,
+    not_posted: bool,
+}
+
+impl Filter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only select sequences tagged with `keyword`.
+    pub fn keyword(mut self, keyword: Keyword) -> Self {
+        self.keywords.push(keyword);
+        self
+    }
+
+    /// Only select sequences not tagged with `keyword`.
+    pub fn without_keyword(mut self, keyword: Keyword) -> Self {
+        self.excluded_keywords.push(keyword);
+        self
+    }
+
+    /// Only select sequences with at least `n` known terms.
+    pub fn min_terms(mut self, n: usize) -> Self {
+        self.min_terms = Some(n);
+        self
+    }
+
+    /// Only select sequences that were never posted.
+    pub fn not_posted(mut self) -> Self {
+        self.not_posted = true;
+        self
+    }
+}
+
+impl Store {
+    /// Open (or create) the database at `path`.
+    pub fn open(path: impl AsRef<Path>) -> rusqlite::Result<Self> {
+        Self::init(Connection::open(path)?)
+    }
+
+    /// Open a temporary database held in memory.
+    pub fn open_in_memory() -> rusqlite::Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> rusqlite::Result<Self> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    /// Update the stored names and terms with the contents of `mirror`.
+    ///
+    /// Sequences saved with [`Store::save_sequence`] keep their keywords,
+    /// and are kept even if they are missing from `mirror`. Other sequences
+    /// missing from `mirror` are removed.
+    pub fn save_mirror(&mut self, mirror: &Mirror) -> rusqlite::Result<()> {
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "DELETE FROM sequences WHERE number NOT IN (SELECT number FROM keywords)",
+            [],
+        )?;
+        {
+            let mut insert = tx.prepare(
+                "INSERT INTO sequences (number, name, data, terms) VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT(number) DO UPDATE SET
+                     name = excluded.name, data = excluded.data, terms = excluded.terms",
+            )?;
+            for (id, entry) in mirror.iter() {
+                insert.execute(params![
+                    id,
+                    entry.name,
+                    join_data(&entry.data),
+                    entry.data.len()
+                ])?;
+            }
+        }
+        tx.commit()
+    }
+
+    /// Insert or update a single sequence, including its keywords.
+    pub fn save_sequence(&mut self, seq: &OeisSequence) -> rusqlite::Result<()> {
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "INSERT OR REPLACE INTO sequences (number, name, data, terms) VALUES (?1, ?2, ?3, ?4)",
+            params![seq.number, seq.name, join_data(&seq.data), seq.data.len()],
+        )?;
+        tx.execute("DELETE FROM keywords WHERE number = ?1", [seq.number])?;
+        for kw in &seq.keyword {
+            tx.execute(
+                "INSERT OR IGNORE INTO keywords (number, keyword) VALUES (?1, ?2)",
+                params![seq.number, kw.as_str()],
+            )?;
+        }
+        tx.commit()
+    }
+
+    /// Look up a sequence by its A-number.
+    pub fn get(&self, id: u64) -> rusqlite::Result<Option<MirrorEntry>> {
+        self.conn
+            .query_row(
+                "SELECT name, data FROM sequences WHERE number = ?1",
+                [id],
+                |row| {
+                    let data: String = row.get(1)?;
+                    Ok(MirrorEntry {
+                        name: row.get(0)?,
+                        data: data.split(',').filter_map(|s| s.parse().ok()).collect(),
+                    })
+                },
+            )
+            .optional()
+    }
+
+    /// Select the A-numbers of the sequences matching `filter`, in
+    /// increasing order.
+    pub fn select(&self, filter: &Filter) -> rusqlite::Result<Vec<u64>> {
+        let mut sql = format!(
+            "SELECT number FROM sequences s WHERE terms >= {}",
+            filter.min_terms.unwrap_or(0)
+        );
+        let mut keywords = Vec::new();
+        for kw in &filter.keywords {
+            keywords.push(kw.as_str());
+            sql.push_str(&format!(
+                " AND EXISTS (SELECT 1 FROM keywords k WHERE k.number = s.number AND k.keyword = ?{})",
+                keywords.len()
+            ));
+        }
+        for kw in &filter.excluded_keywords {
+            keywords.push(kw.as_str());
+            sql.push_str(&format!(
+                " AND NOT EXISTS (SELECT 1 FROM keywords k WHERE k.number = s.number AND k.keyword = ?{})",
+                keywords.len()
+            ));
+        }
+        if filter.not_posted {
+            sql.push_str(" AND NOT EXISTS (SELECT 1 FROM posts p WHERE p.number = s.number)");
+        }
+        sql.push_str(" ORDER BY number");
+        let mut stmt = self.conn.prepare(&sql)?;
+        stmt.query_map(params_from_iter(keywords), |row| row.get(0))?
+            .collect()
+    }
+
+    /// Record that sequence `id` was just posted.
+    pub fn record_post(&self, id: u64) -> rusqlite::Result<()> {
+        self.conn
+            .execute("INSERT INTO posts (number) VALUES (?1)", [id])?;
+        Ok(())
+    }
+
+    /// Number of times sequence `id` was posted.
+    pub fn post_count(&self, id: u64) -> rusqlite::Result<u64> {
+        self.conn.query_row(
+            "SELECT COUNT(*) FROM posts WHERE number = ?1",
+            [id],
+            |row| row.get(0),
+        )
+    }
+
+    /// Attach `text` to sequence `id`, to be included when it is posted.
+    /// Replaces any previous commentary.
+    pub fn set_commentary(&self, id: u64, text: &str) -> rusqlite::Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO commentary (number, text) VALUES (?1, ?2)",
+            params![id, text],
+        )?;
+        Ok(())
+    }
+
+    /// Remove the commentary attached to sequence `id`, if any.
+    pub fn remove_commentary(&self, id: u64) -> rusqlite::Result<()> {
+        self.conn
+            .execute("DELETE FROM commentary WHERE number = ?1", [id])?;
+        Ok(())
+    }
+
+    /// Commentary attached to sequence `id`.
+    pub fn commentary(&self, id: u64) -> rusqlite::Result<Option<String>> {
+        self.conn
+            .query_row(
+                "SELECT text FROM commentary WHERE number = ?1",
+                [id],
+                |row| row.get(0),
+            )
+            .optional()
+    }
+
+    /// Add the formatted `status` of sequence `id` to the fallback pool.
+    pub fn add_to_pool(&self, id: u64, status: &str) -> rusqlite::Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO pool (number, status) VALUES (?1, ?2)",
+            params![id, status],
+        )?;
+        Ok(())
+    }
+
+    /// Remove a random status from the fallback pool, and return it with
+    /// the A-number of its sequence.
+    pub fn take_from_pool(&mut self) -> rusqlite::Result<Option<(u64, String)>> {
+        let tx = self.conn.transaction()?;
+        let entry = tx
+            .query_row(
+                "SELECT number, status FROM pool ORDER BY RANDOM() LIMIT 1",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        if let Some((id, _)) = &entry {
+            tx.execute("DELETE FROM pool WHERE number = ?1", [id])?;
+        }
+        tx.commit()?;
+        Ok(entry)
+    }
+
+    /// Number of statuses in the fallback pool.
+    pub fn pool_len(&self) -> rusqlite::Result<usize> {
+        self.conn
+            .query_row("SELECT COUNT(*) FROM pool", [], |row| row.get(0))
+    }
+}
+
+fn join_data(data: &[BigInt]) -> String {
+    let data: Vec<String> = data.iter().map(|n| n.to_string()).collect();
+    data.join(",")
+}
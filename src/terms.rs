@@ -0,0 +1,59 @@
+//! Reading lists of terms typed or pasted by users, e.g. to find which
+//! sequences they come from with
+//! [`Client::search_by_terms`](crate::fetch::Client::search_by_terms) or
+//! [`Mirror::identify_offline`](crate::mirror::Mirror::identify_offline).
+
+use num_bigint::BigInt;
+use std::fmt;
+use std::io::{self, Read};
+
+#[derive(Debug)]
+pub enum ReadTermsError {
+    Io(io::Error),
+    /// A token that is not an integer.
+    InvalidTerm(String),
+}
+
+impl fmt::Display for ReadTermsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadTermsError::Io(e) => write!(f, "I/O error while reading terms: {e}"),
+            ReadTermsError::InvalidTerm(term) => write!(f, "invalid term: {term:?}"),
+        }
+    }
+}
+
+impl std::error::Error for ReadTermsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReadTermsError::Io(e) => Some(e),
+            ReadTermsError::InvalidTerm(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for ReadTermsError {
+    fn from(e: io::Error) -> Self {
+        ReadTermsError::Io(e)
+    }
+}
+
+/// Read the terms in `reader` (e.g. standard input), separated by commas
+/// and/or whitespace, e.g. `1, 1, 2, 3, 5`. Terms may be arbitrarily large.
+pub fn read_terms(mut reader: impl Read) -> Result<Vec<BigInt>, ReadTermsError> {
+    let mut text = String::new();
+    reader.read_to_string(&mut text)?;
+    parse_terms(&text)
+}
+
+/// Parse terms separated by commas and/or whitespace, see [`read_terms`].
+pub fn parse_terms(text: &str) -> Result<Vec<BigInt>, ReadTermsError> {
+    text.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|token| !token.is_empty())
+        .map(|token| {
+            token
+                .parse()
+                .map_err(|_| ReadTermsError::InvalidTerm(token.to_owned()))
+        })
+        .collect()
+}
@@ -0,0 +1,72 @@
+//! Unicode-aware text measurement and truncation.
+//!
+//! Text is cut between grapheme clusters, so that combining characters stay
+//! with their base character, and measured in columns, so that wide (e.g.
+//! CJK) characters count twice.
+
+use std::borrow::Cow;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// The ellipsis appended to truncated text.
+pub const ELLIPSIS: char = '…';
+
+/// Width of `s` when displayed, in columns.
+pub fn width(s: &str) -> usize {
+    s.width()
+}
+
+/// The longest prefix of `s` made of whole grapheme clusters and at most
+/// `max_width` columns wide.
+pub fn truncate(s: &str, max_width: usize) -> &str {
+    let mut used = 0;
+    for (i, grapheme) in s.grapheme_indices(true) {
+        used += grapheme.width();
+        if used > max_width {
+            return &s[..i];
+        }
+    }
+    s
+}
+
+/// `s` if it is at most `max_width` columns wide, otherwise its longest
+/// prefix followed by an ellipsis fitting in `max_width` columns.
+pub fn truncate_with_ellipsis(s: &str, max_width: usize) -> Cow<'_, str> {
+    if width(s) <= max_width {
+        return Cow::Borrowed(s);
+    }
+    let mut short = truncate(s, max_width.saturating_sub(1)).to_owned();
+    short.push(ELLIPSIS);
+    Cow::Owned(short)
+}
+
+/// Shorten a sequence name to at most `max_width` columns, cutting it after
+/// its last clause (delimited by `,`, `;` or `:` outside brackets) that
+/// fits, so that at least the leading description of the object is kept.
+/// Names without such a clause are truncated like
+/// [`truncate_with_ellipsis`].
+pub fn shorten_name(name: &str, max_width: usize) -> Cow<'_, str> {
+    if width(name) <= max_width {
+        return Cow::Borrowed(name);
+    }
+    let budget = max_width.saturating_sub(1);
+    let mut depth = 0usize;
+    let mut cut = None;
+    for (i, c) in name.char_indices() {
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth = depth.saturating_sub(1),
+            ',' | ';' | ':' if depth == 0 && name[i + 1..].starts_with(' ') => {
+                if width(&name[..i]) > budget {
+                    break;
+                }
+                cut = Some(i);
+            }
+            _ => {}
+        }
+    }
+    match cut {
+        Some(i) => Cow::Owned(format!("{}{ELLIPSIS}", name[..i].trim_end())),
+        None => truncate_with_ellipsis(name, max_width),
+    }
+}
@@ -0,0 +1,139 @@
+//! A client for oeis.org running in web browsers, based on the `fetch()`
+//! API through `gloo-net`, for builds targeting `wasm32-unknown-unknown`.
+//! Build with `--no-default-features --features wasm`, as the default
+//! transport does not compile to WebAssembly.
+//!
+//! Like [`AsyncClient`](crate::asynchronous::AsyncClient), it does not
+//! retry failed requests nor cache responses. The browser enforces CORS, so
+//! requests may have to go through a proxy, see [`WasmClient::with_search_url`].
+
+use crate::error::{FetchError, SelectionError, SelectionStats};
+use crate::fetch::{
+    MAX_RANDOM_ATTEMPTS, MAX_SEQUENCE_ID, MAX_SEQUENCE_ID_TTL, REJECTED_KEYWORD_SET, SEARCH_URL,
+    check_number, is_html,
+};
+use crate::oeis::{Keyword, OeisEntry, OeisSequence, SearchPage};
+use crate::query::{Query, Sort};
+use gloo_net::http::Request;
+use rand::Rng;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+/// A client for oeis.org using the browser's `fetch()`.
+#[derive(Debug, Clone)]
+pub struct WasmClient {
+    search_url: String,
+    max_id: Arc<Mutex<Option<(u64, SystemTime)>>>,
+}
+
+impl Default for WasmClient {
+    fn default() -> Self {
+        Self {
+            search_url: SEARCH_URL.to_owned(),
+            max_id: Arc::default(),
+        }
+    }
+}
+
+impl WasmClient {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Send searches to `url` instead of `https://oeis.org/search`, e.g. to a
+    /// proxy adding CORS headers.
+    pub fn with_search_url(mut self, url: &str) -> Self {
+        self.search_url = url.to_owned();
+        self
+    }
+
+    /// Fetch a sequence from oeis.org by its A-number.
+    ///
+    /// Fails with [`FetchError::Mismatch`] if the OEIS answers with another
+    /// sequence.
+    pub async fn fetch(&self, id: u64) -> Result<OeisSequence, FetchError> {
+        let entry = self
+            .search_page(&Query::new().id(id))
+            .await?
+            .into_iter()
+            .next()
+            .ok_or(FetchError::NotFound(id))?;
+        check_number(id, entry.number)?;
+        Ok(OeisSequence::try_from(entry)?.fetched_at(now()))
+    }
+
+    /// The first page of results of a search query.
+    async fn search_page(&self, query: &Query) -> Result<Vec<OeisEntry>, FetchError> {
+        let q = query.to_string();
+        let mut params = vec![("q", q.as_str()), ("fmt", "json")];
+        if let Some(sort) = query.sort_order() {
+            params.push(("sort", sort.as_str()));
+        }
+        let response = Request::get(&self.search_url).query(params).send().await?;
+        if !response.ok() {
+            return Err(FetchError::Status(response.status()));
+        }
+        let body = response.text().await?;
+        if is_html(&body) {
+            return Err(FetchError::ServiceUnavailable);
+        }
+        let page = SearchPage::from_json(&body)?;
+        Ok(page.into_parts().0)
+    }
+
+    /// The largest A-number in the OEIS, like
+    /// [`Client::max_sequence_id`](crate::fetch::Client::max_sequence_id).
+    pub async fn max_sequence_id(&self) -> Result<u64, FetchError> {
+        let now = now();
+        if let Some((id, discovered_at)) = *self.max_id.lock().unwrap()
+            && now < discovered_at + MAX_SEQUENCE_ID_TTL
+        {
+            return Ok(id);
+        }
+        let query = Query::new().keyword(Keyword::New).sort(Sort::Created);
+        let id = self
+            .search_page(&query)
+            .await?
+            .iter()
+            .map(|entry| entry.number)
+            .max()
+            .ok_or_else(|| FetchError::NoResults(query.to_string()))?;
+        *self.max_id.lock().unwrap() = Some((id, now));
+        Ok(id)
+    }
+
+    /// Fetch a random sequence, like
+    /// [`Client::fetch_random`](crate::fetch::Client::fetch_random).
+    pub async fn fetch_random(&self) -> Result<OeisSequence, SelectionError> {
+        let max_id = self.max_sequence_id().await.unwrap_or(MAX_SEQUENCE_ID);
+        let mut stats = SelectionStats::default();
+        while stats.attempts < MAX_RANDOM_ATTEMPTS {
+            stats.attempts += 1;
+            let id = rand::rng().random_range(1..=max_id);
+            let seq = match self.fetch(id).await {
+                Ok(seq) => seq,
+                Err(FetchError::NotFound(_) | FetchError::Mismatch { .. }) => {
+                    stats.not_found += 1;
+                    continue;
+                }
+                Err(FetchError::InvalidEntry(_)) => {
+                    stats.invalid += 1;
+                    continue;
+                }
+                Err(e) => return Err(e.into()),
+            };
+            if seq.keyword_set().intersects(REJECTED_KEYWORD_SET) {
+                stats.rejected += 1;
+                continue;
+            }
+            return Ok(seq);
+        }
+        Err(SelectionError::Exhausted(stats))
+    }
+}
+
+/// The current time, from JavaScript as `SystemTime::now` is not supported
+/// in browsers.
+fn now() -> SystemTime {
+    SystemTime::UNIX_EPOCH + Duration::from_secs_f64(js_sys::Date::now() / 1000.0)
+}
@@ -0,0 +1,88 @@
+use crate::http::{self, HttpTransport, Request};
+use crate::mastodon::PostError;
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::Sha256;
+use std::sync::Arc;
+
+/// Outcome of an attempt to post a status, sent to webhooks.
+#[derive(Debug, Clone, Serialize)]
+pub struct PostEvent {
+    /// The A-number of the sequence.
+    pub sequence: u64,
+    /// URL of the posted status, if posting succeeded.
+    pub status_url: Option<String>,
+    /// Where the status was posted (e.g. `mastodon`).
+    pub backend: String,
+    /// Why posting failed, if it did.
+    pub error: Option<String>,
+}
+
+/// URLs notified of [`PostEvent`]s with a JSON `POST` request.
+///
+/// If a secret is set, requests have an `X-OEIS-Bot-Signature` header
+/// holding `sha256=` followed by the hex-encoded HMAC-SHA256 of the body,
+/// so that receivers can check where events come from.
+#[derive(Debug, Clone)]
+pub struct Webhooks {
+    urls: Vec<String>,
+    secret: Option<String>,
+    transport: Arc<dyn HttpTransport>,
+}
+
+impl Webhooks {
+    pub fn new(urls: Vec<String>) -> Self {
+        Self {
+            urls,
+            secret: None,
+            transport: http::default_transport(),
+        }
+    }
+
+    /// Sign requests with `secret`.
+    pub fn with_secret(mut self, secret: &str) -> Self {
+        self.secret = Some(secret.to_owned());
+        self
+    }
+
+    /// Send requests with `transport`.
+    pub fn with_transport(mut self, transport: Arc<dyn HttpTransport>) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    /// Send `event` to every URL. Returns the URLs that could not be
+    /// notified, with the corresponding errors.
+    pub fn notify(&self, event: &PostEvent) -> Vec<(&str, PostError)> {
+        let body = serde_json::to_vec(event).expect("events serialize to JSON");
+        let signature = self.secret.as_ref().map(|secret| sign(secret, &body));
+        let mut errors = Vec::new();
+        for url in &self.urls {
+            let mut req = Request::post(url).header("Content-Type", "application/json");
+            if let Some(signature) = &signature {
+                req = req.header("X-OEIS-Bot-Signature", signature);
+            }
+            let result = match self.transport.send(req.body(body.clone())) {
+                Ok(response) if response.is_success() => continue,
+                Ok(response) => PostError::Status(response.status),
+                Err(e) => e.into(),
+            };
+            errors.push((url.as_str(), result));
+        }
+        errors
+    }
+}
+
+/// The signature header value of `body`.
+fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(body);
+    let digest: String = mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+    format!("sha256={digest}")
+}
@@ -0,0 +1,151 @@
+use crate::oeis::{ANumber, OeisSequence};
+use std::collections::BTreeMap;
+
+/// Limits on the traversal of cross-references by
+/// [`Client::fetch_xrefs`](crate::fetch::Client::fetch_xrefs).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct XrefLimits {
+    /// Maximum number of hops from the starting sequence.
+    pub depth: usize,
+    /// Maximum number of sequences fetched, excluding the starting one.
+    pub max_sequences: usize,
+}
+
+impl Default for XrefLimits {
+    /// Direct cross-references only, at most 20 of them.
+    fn default() -> Self {
+        Self {
+            depth: 1,
+            max_sequences: 20,
+        }
+    }
+}
+
+/// Sequences reachable from a starting sequence through their
+/// cross-references.
+#[derive(Debug, Clone, Default)]
+pub struct XrefGraph {
+    /// The A-number of the starting sequence.
+    pub root: u64,
+    /// The fetched sequences, including the starting one, by A-number.
+    pub sequences: BTreeMap<u64, OeisSequence>,
+    /// The A-numbers referenced by each fetched sequence whose
+    /// cross-references were followed, in order of appearance. They may
+    /// not all have been fetched.
+    pub edges: BTreeMap<u64, Vec<u64>>,
+}
+
+impl XrefGraph {
+    /// The A-numbers referenced by `id` that were fetched.
+    pub fn neighbors(&self, id: u64) -> impl Iterator<Item = u64> {
+        self.edges
+            .get(&id)
+            .into_iter()
+            .flatten()
+            .copied()
+            .filter(|id| self.sequences.contains_key(id))
+    }
+}
+
+/// A-numbers mentioned in the cross-references of `seq`, in order of
+/// appearance and without duplicates, excluding `seq` itself.
+pub fn xref_ids(seq: &OeisSequence) -> Vec<u64> {
+    let mut ids = Vec::new();
+    for (_, id) in seq.xref.iter().flat_map(|line| a_numbers(line)) {
+        if id != seq.number && !ids.contains(&id) {
+            ids.push(id);
+        }
+    }
+    ids
+}
+
+/// A line of the cross-references of a sequence, e.g. `Partial sums of
+/// A000045.`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Xref {
+    /// The relation to the referenced sequences: the text before the first
+    /// A-number, without a trailing colon (e.g. `Cf.`, `Partial sums of`).
+    pub kind: String,
+    /// The referenced A-numbers, in order of appearance.
+    pub ids: Vec<ANumber>,
+}
+
+/// The lines of the cross-references of `seq` that mention A-numbers, in
+/// order.
+pub fn xrefs(seq: &OeisSequence) -> Vec<Xref> {
+    seq.xref
+        .iter()
+        .filter_map(|line| {
+            let ids: Vec<_> = a_numbers(line).collect();
+            let (start, _) = *ids.first()?;
+            Some(Xref {
+                kind: line[..start].trim().trim_end_matches(':').to_owned(),
+                ids: ids.into_iter().map(|(_, id)| ANumber(id)).collect(),
+            })
+        })
+        .collect()
+}
+
+/// The A-numbers mentioned in `text`, with their byte positions.
+fn a_numbers(text: &str) -> impl Iterator<Item = (usize, u64)> + '_ {
+    let bytes = text.as_bytes();
+    text.match_indices('A').filter_map(move |(i, _)| {
+        // An A-number is an A followed by at least 6 digits, not part of a
+        // longer word.
+        let digits = &bytes[i + 1..];
+        let n = digits.iter().take_while(|b| b.is_ascii_digit()).count();
+        let in_word = |j: usize| bytes.get(j).is_some_and(u8::is_ascii_alphanumeric);
+        if n < ANumber::MIN_DIGITS || (i > 0 && in_word(i - 1)) || in_word(i + 1 + n) {
+            return None;
+        }
+        let id = text[i + 1..i + 1 + n].parse().ok()?;
+        Some((i, id))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::internal::parse_internal;
+
+    fn sequence() -> OeisSequence {
+        let text = "\
+%S A000045 0,1,1,2,3,5,8,13
+%N A000045 Fibonacci numbers.
+%Y A000045 Cf. A000032, A000204.
+%Y A000045 Partial sums of A000045: A000071.
+%Y A000045 Sequence in context: A039834 A236191 A000032
+%Y A000045 No A-numbers in A12345, BA000001 or A000001x.
+%O A000045 0,4
+%K A000045 core,nonn
+";
+        OeisSequence::try_from(parse_internal(text).unwrap().remove(0)).unwrap()
+    }
+
+    #[test]
+    fn xref_lines() {
+        let ids = |ids: &[u64]| ids.iter().copied().map(ANumber).collect::<Vec<_>>();
+        assert_eq!(
+            xrefs(&sequence()),
+            [
+                Xref {
+                    kind: "Cf.".to_owned(),
+                    ids: ids(&[32, 204]),
+                },
+                Xref {
+                    kind: "Partial sums of".to_owned(),
+                    ids: ids(&[45, 71]),
+                },
+                Xref {
+                    kind: "Sequence in context".to_owned(),
+                    ids: ids(&[39834, 236191, 32]),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn referenced_ids() {
+        assert_eq!(xref_ids(&sequence()), [32, 204, 71, 39834, 236191]);
+    }
+}